@@ -0,0 +1,74 @@
+use std::io::{self, Write};
+
+use crate::vault::SourcedEvent;
+
+fn event_line(sourced: &SourcedEvent) -> String {
+  /* `Sat 15 Feb 2024  6:00 PM-10:00 PM  The Plot in You @ White Oak`, names and clock from the note's locale */
+  let em = &sourced.event;
+  let locale = em.locale();
+  let mut ret = locale.format_date(em.start_date());
+  if em.end_date() != em.start_date() && em.end_time().is_none() {
+    ret.push_str(&format!(" - {}", locale.format_date(em.end_date())));
+  }
+
+  match (em.start_time(), em.end_time()) {
+    _ if em.time_tba() => ret.push_str("  TBA"),
+    (Some(start), Some(end)) if em.end_date() != em.start_date() => {
+      ret.push_str(&format!("  {} - {} {}", locale.format_time(start), locale.format_date(em.end_date()), locale.format_time(end)));
+    },
+    (Some(start), Some(end)) => ret.push_str(&format!("  {}-{}", locale.format_time(start), locale.format_time(end))),
+    (Some(start), None) => ret.push_str(&format!("  {}", locale.format_time(start))),
+    _ => {},
+  }
+  if let Some(tz) = em.timezone() {
+    ret.push_str(&format!(" {}", tz.name()));
+  }
+
+  ret.push_str(&format!("  {}", em.title()));
+  if !em.place().is_empty() {
+    ret.push_str(&format!(" @ {}", em.place()));
+  }
+  ret
+}
+
+pub fn write_agenda<'a, W: Write>(out: &mut W, events: impl IntoIterator<Item = &'a SourcedEvent>) -> io::Result<()> {
+  /* One line per event in date order, for reading rather than for other programs */
+  let mut events: Vec<&SourcedEvent> = events.into_iter().collect();
+  events.sort_by_key(|s| (s.event.start_date(), s.event.start_time()));
+  for sourced in events {
+    writeln!(out, "{}", event_line(sourced))?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::agenda::*;
+  use crate::config::ParseConfig;
+  use crate::locale::Locale;
+  use crate::model::EventModel;
+  use crate::vault::tests::sourced;
+
+  fn event(line: &str, locale: Locale) -> SourcedEvent {
+    let config = ParseConfig { locale, ..Default::default() };
+    sourced(EventModel::from_line_with(line.to_string(), &config).unwrap())
+  }
+
+  #[test]
+  fn test_agenda() {
+    let events = [
+      event("- [ ] (24-25 Feb 2024) () () Excision", Locale::En),
+      event("- [ ] (15 Feb 2024) (6-10PM CST) (White Oak Music Hall) The Plot in You", Locale::En),
+      event("- [ ] (3 März 2024) (22:00-02:00) (Berghain) Nachtschicht", Locale::De),
+      event("- [ ] (1 mar 2024) (TBA) () Concierto", Locale::Es),
+    ];
+    let mut out = Vec::new();
+    write_agenda(&mut out, &events).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "\
+Thu 15 Feb 2024  6:00 PM-10:00 PM America/Chicago  The Plot in You @ White Oak Music Hall
+Sat 24 Feb 2024 - Sun 25 Feb 2024  Excision
+vie 1 mar 2024  TBA  Concierto
+So 3 Mär 2024  22:00 - Mo 4 Mär 2024 02:00  Nachtschicht @ Berghain
+");
+  }
+}
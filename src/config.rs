@@ -0,0 +1,188 @@
+use chrono::{Datelike, NaiveDate, ParseResult, Utc};
+use chrono_tz::Tz;
+
+use crate::locale::Locale;
+use crate::note::NoteMeta;
+
+// How to fill in the year for dates like `(21 Nov)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YearPolicy {
+  Next, // First occurrence on or after the reference date, good for a list of upcoming shows
+  #[default]
+  Nearest, // Whichever occurrence is closest to the reference date, past or future
+  NoteCreated, // The year the note was created in
+  Fixed(i32),
+}
+
+impl std::str::FromStr for YearPolicy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<YearPolicy, String> {
+    match s {
+      "next" => Ok(YearPolicy::Next),
+      "nearest" => Ok(YearPolicy::Nearest),
+      "note" => Ok(YearPolicy::NoteCreated),
+      year => year.parse().map(YearPolicy::Fixed).map_err(|_| format!("unknown year policy {year}, expected next, nearest, note or a year")),
+    }
+  }
+}
+
+// How to read an ambiguous numeric date like `(2/3)`, dotted dates like `(2.3.2024)` are always day first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateOrder {
+  #[default]
+  MonthFirst, // 2/3 is 3 Feb
+  DayFirst, // 2/3 is 2 Mar
+}
+
+impl std::str::FromStr for DateOrder {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<DateOrder, String> {
+    match s {
+      "month-first" | "mdy" => Ok(DateOrder::MonthFirst),
+      "day-first" | "dmy" => Ok(DateOrder::DayFirst),
+      other => Err(format!("unknown date order {other}, expected month-first or day-first")),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseConfig {
+  pub default_tz: Option<Tz>, // For events whose line and note don't name a zone
+  pub year_policy: YearPolicy,
+  pub reference_date: Option<NaiveDate>, // What "now" is for year inference, the note's creation date or today if None
+  pub note_created: Option<NaiveDate>,
+  pub date_order: DateOrder, // For slash dates only
+  pub locale: Locale, // Month and weekday names, a note's `lang:` line overrides it
+  pub two_digit_pivot: Option<i32>, // `24` below this is 2024, at or above it 19xx, DEFAULT_PIVOT if None
+  pub event_tag: Option<String>, // The tag, without `#`, that marks a note as holding events, DEFAULT_EVENT_TAG if None
+}
+
+pub const DEFAULT_PIVOT: i32 = 70;
+pub const DEFAULT_EVENT_TAG: &str = "event";
+
+impl ParseConfig {
+  pub fn reference(&self) -> NaiveDate {
+    self.reference_date.unwrap_or_else(|| Utc::now().date_naive())
+  }
+
+  pub fn infer_year(&self, date_in_year: impl Fn(i32) -> ParseResult<NaiveDate>) -> ParseResult<NaiveDate> {
+    /*
+     * date_in_year builds the date for a candidate year. Candidates that don't exist (29 Feb) are skipped,
+     * if none work the first error is returned so the caller still gets a chrono error to report.
+     */
+    let reference = self.reference();
+    let candidates: Vec<ParseResult<NaiveDate>> = match self.year_policy {
+      YearPolicy::Fixed(year) => vec![date_in_year(year)],
+      YearPolicy::NoteCreated => vec![date_in_year(self.note_created.unwrap_or(reference).year())],
+      // Far enough ahead that a leap day always finds its year
+      YearPolicy::Next => (reference.year()..=reference.year() + 8).map(&date_in_year).collect(),
+      YearPolicy::Nearest => (reference.year() - 1..=reference.year() + 1).map(&date_in_year).collect(),
+    };
+
+    let found = match self.year_policy {
+      YearPolicy::Next => candidates.iter().flatten().find(|d| **d >= reference).copied(),
+      YearPolicy::Nearest => candidates.iter().flatten().min_by_key(|d| (**d - reference).num_days().abs()).copied(),
+      YearPolicy::Fixed(_) | YearPolicy::NoteCreated => candidates.iter().flatten().next().copied(),
+    };
+    match found {
+      Some(date) => Ok(date),
+      None => candidates.into_iter().find(|c| c.is_err()).unwrap_or_else(|| date_in_year(reference.year())),
+    }
+  }
+
+  pub fn event_tag(&self) -> &str {
+    self.event_tag.as_deref().unwrap_or(DEFAULT_EVENT_TAG)
+  }
+
+  pub fn expand_year(&self, year: i32) -> i32 {
+    /* Two digit years go through the pivot window, ex with a pivot of 70 `69` is 2069 and `70` is 1970 */
+    let pivot = self.two_digit_pivot.unwrap_or(DEFAULT_PIVOT);
+    match year {
+      0..=99 if year < pivot => 2000 + year,
+      0..=99 => 1900 + year,
+      year => year,
+    }
+  }
+
+  pub fn for_note(&self, meta: &NoteMeta) -> ParseConfig {
+    /*
+     * The note's own settings fill in whatever wasn't set globally: its zone replaces the default one,
+     * and its creation date becomes the reference date unless one was given explicitly. Its language wins
+     * over the vault's, a note knows what it's written in.
+     */
+    let mut ret = self.clone();
+    ret.default_tz = meta.timezone.or(self.default_tz);
+    ret.locale = meta.locale.unwrap_or(self.locale);
+    ret.note_created = meta.created_date().or(self.note_created);
+    ret.reference_date = self.reference_date.or(ret.note_created);
+    ret
+  }
+}
+
+// What people actually write after a time. These map to the zone they mean, not a fixed offset,
+// so `6PM CST` on a July date still comes out as 6PM CDT
+const TZ_ABBREVIATIONS: [(&str, Tz); 20] = [
+  ("ET", Tz::America__New_York), ("EST", Tz::America__New_York), ("EDT", Tz::America__New_York),
+  ("CT", Tz::America__Chicago), ("CST", Tz::America__Chicago), ("CDT", Tz::America__Chicago),
+  ("MT", Tz::America__Denver), ("MST", Tz::America__Denver), ("MDT", Tz::America__Denver),
+  ("PT", Tz::America__Los_Angeles), ("PST", Tz::America__Los_Angeles), ("PDT", Tz::America__Los_Angeles),
+  ("AKST", Tz::America__Anchorage), ("AKDT", Tz::America__Anchorage),
+  ("HST", Tz::Pacific__Honolulu),
+  ("UTC", Tz::UTC), ("GMT", Tz::UTC), ("Z", Tz::UTC),
+  ("BST", Tz::Europe__London),
+  ("CET", Tz::Europe__Paris),
+];
+
+pub fn parse_tz(name: &str) -> Option<Tz> {
+  /* Abbreviations first since chrono-tz has a few legacy fixed-offset zones under the same names (EST, MST, HST) */
+  TZ_ABBREVIATIONS.iter()
+    .find(|(abbr, _)| abbr.eq_ignore_ascii_case(name))
+    .map(|(_, tz)| *tz)
+    .or_else(|| name.parse::<Tz>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::config::*;
+
+  #[test]
+  fn test_parse_tz() {
+    assert_eq!(parse_tz("CST"), Some(Tz::America__Chicago));
+    assert_eq!(parse_tz("cdt"), Some(Tz::America__Chicago));
+    assert_eq!(parse_tz("America/Chicago"), Some(Tz::America__Chicago));
+    assert_eq!(parse_tz("Europe/Berlin"), Some(Tz::Europe__Berlin));
+    assert_eq!(parse_tz("PM"), None);
+    assert_eq!(parse_tz("Houston"), None);
+  }
+
+  #[test]
+  fn test_year_policy_from_str() {
+    assert_eq!("next".parse(), Ok(YearPolicy::Next));
+    assert_eq!("note".parse(), Ok(YearPolicy::NoteCreated));
+    assert_eq!("2023".parse(), Ok(YearPolicy::Fixed(2023)));
+    assert!("soon".parse::<YearPolicy>().is_err());
+  }
+
+  #[test]
+  fn test_date_order_from_str() {
+    assert_eq!("day-first".parse(), Ok(DateOrder::DayFirst));
+    assert_eq!("mdy".parse(), Ok(DateOrder::MonthFirst));
+    assert!("ymd".parse::<DateOrder>().is_err());
+  }
+
+  #[test]
+  fn test_expand_year() {
+    let config = ParseConfig::default();
+    assert_eq!(config.expand_year(24), 2024);
+    assert_eq!(config.expand_year(69), 2069);
+    assert_eq!(config.expand_year(70), 1970);
+    assert_eq!(config.expand_year(0), 2000);
+    assert_eq!(config.expand_year(2023), 2023);
+
+    let config = ParseConfig { two_digit_pivot: Some(30), ..Default::default() };
+    assert_eq!(config.expand_year(24), 2024);
+    assert_eq!(config.expand_year(45), 1945);
+  }
+}
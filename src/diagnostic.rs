@@ -0,0 +1,176 @@
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::model::{EventParseError, Location};
+use crate::vault::ScanFailure;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const BLUE: &str = "\x1b[1;34m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+
+// Same as rustc, tabs are drawn as 4 spaces so the carets line up
+const TAB: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  Warning,
+  Error,
+}
+
+impl Severity {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Severity::Warning => "warning",
+      Severity::Error => "error",
+    }
+  }
+
+  fn color(&self) -> &'static str {
+    match self {
+      Severity::Warning => YELLOW,
+      Severity::Error => RED,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub rule: &'static str,
+  pub message: String,
+  pub path: PathBuf,
+  pub line: usize,
+  pub text: String, // The whole source line, span indexes into it
+  pub span: Option<Range<usize>>,
+  pub help: Option<String>,
+}
+
+impl Diagnostic {
+  pub fn from_error(path: &Path, line: usize, text: &str, error: &EventParseError) -> Diagnostic {
+    Diagnostic {
+      severity: Severity::Error,
+      rule: error.rule(),
+      message: error.to_string(),
+      path: path.to_path_buf(),
+      line,
+      text: text.to_string(),
+      span: error.span(),
+      help: Some(error.help().to_owned()),
+    }
+  }
+
+  pub fn from_failure(failure: &ScanFailure) -> Diagnostic {
+    Diagnostic::from_error(&failure.path, failure.line, &failure.text, &failure.error)
+  }
+
+  pub fn loc(&self) -> Location {
+    Location { line: Some(self.line), span: self.span.clone() }
+  }
+
+  pub fn column(&self) -> usize {
+    self.loc().column(&self.text).unwrap_or(1)
+  }
+
+  pub fn end_column(&self) -> usize {
+    self.loc().end_column(&self.text).unwrap_or_else(|| self.column())
+  }
+
+  pub fn render(&self, color: bool) -> String {
+    /*
+     * Looks like rustc's output:
+     *
+     * error[bad-time]: invalid time `25 PM`
+     *   --> Concerts.md:12:22
+     *    |
+     * 12 | - [ ] (15 Feb 2024) (25 PM) () The Plot in You
+     *    |                      ^^^^^
+     *    = help: expected `6 PM`, `6:30 PM`, `6-10PM` or `18:00-22:30`
+     */
+    let paint = |code: &'static str| if color { code } else { "" };
+    let (reset, bold, blue, sev) = (paint(RESET), paint(BOLD), paint(BLUE), paint(self.severity.color()));
+
+    let gutter = self.line.to_string().len();
+    let pad = " ".repeat(gutter);
+    let mut out = String::new();
+
+    writeln!(out, "{sev}{}[{}]{reset}{bold}: {}{reset}", self.severity.as_str(), self.rule, self.message).unwrap();
+    writeln!(out, "{pad}{blue}-->{reset} {}:{}:{}", self.path.display(), self.line, self.column()).unwrap();
+    writeln!(out, "{pad} {blue}|{reset}").unwrap();
+    writeln!(out, "{blue}{}{reset} {blue}|{reset} {}", self.line, self.text.replace('\t', TAB)).unwrap();
+
+    if let Some(span) = &self.span {
+      let (indent, width) = self.underline(span);
+      writeln!(out, "{pad} {blue}|{reset} {}{sev}{}{reset}", " ".repeat(indent), "^".repeat(width.max(1))).unwrap();
+    }
+    if let Some(help) = &self.help {
+      writeln!(out, "{pad} {blue}={reset} {bold}help{reset}: {help}").unwrap();
+    }
+    out
+  }
+
+  fn underline(&self, span: &Range<usize>) -> (usize, usize) {
+    /* Display columns before and inside the span, an empty span (like a missing title) still gets one caret */
+    let width_of = |s: &str| s.chars().map(|c| if c == '\t' { TAB.len() } else { 1 }).sum::<usize>();
+    let before = self.text.get(..span.start).unwrap_or("");
+    let inside = self.text.get(span.clone()).unwrap_or("");
+    (width_of(before), width_of(inside))
+  }
+}
+
+pub fn use_color(choice: Option<&str>) -> Result<bool, String> {
+  /* `auto` only colors when stderr is a terminal and NO_COLOR (https://no-color.org) isn't set */
+  use std::io::IsTerminal;
+  match choice.unwrap_or("auto") {
+    "always" => Ok(true),
+    "never" => Ok(false),
+    "auto" => Ok(std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()),
+    other => Err(format!("unknown color choice {other}, expected auto, always or never")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::diagnostic::*;
+  use crate::model::EventModel;
+
+  fn failure(text: &str, line: usize) -> Diagnostic {
+    let error = EventModel::from_line(text.to_string()).unwrap_err().with_line(line);
+    Diagnostic::from_failure(&ScanFailure { path: PathBuf::from("Concerts.md"), line, text: text.to_string(), error })
+  }
+
+  #[test]
+  fn test_render_bad_time() {
+    let diag = failure("- [ ] (15 Feb 2024) (6ish) (White Oak) The Plot in You", 12);
+    let expected = "\
+error[bad-time]: invalid time `6ish`
+  --> Concerts.md:12:22
+   |
+12 | - [ ] (15 Feb 2024) (6ish) (White Oak) The Plot in You
+   |                      ^^^^
+   = help: expected `6 PM`, `6:30 PM`, `6-10PM` or `18:00-22:30`
+";
+    assert_eq!(diag.render(false), expected);
+  }
+
+  #[test]
+  fn test_render_tabs_and_empty_span() {
+    let diag = failure("\t- [ ] (2 Nov) () (Houston) ", 3);
+    let rendered = diag.render(false);
+    assert!(rendered.starts_with("error[empty-title]: event has no title\n"));
+    assert!(rendered.contains("\n3 |     - [ ] (2 Nov) () (Houston) \n"));
+    // 4 columns for the tab plus the 27 chars before the (empty) title
+    assert!(rendered.contains(&format!("\n  | {}^\n", " ".repeat(31))));
+  }
+
+  #[test]
+  fn test_render_color() {
+    let diag = failure("- [ ] (31 Feb 2024) () () Nope", 1);
+    let rendered = diag.render(true);
+    assert!(rendered.starts_with("\x1b[1;31merror[bad-date]\x1b[0m"));
+    assert!(rendered.contains("\x1b[1;31m^^^^^^^^^^^\x1b[0m"));
+    assert!(!diag.render(false).contains('\x1b'));
+  }
+}
@@ -0,0 +1,235 @@
+use std::io::{self, Write};
+
+use chrono::{Days, NaiveDateTime, Utc};
+
+use chrono::TimeZone;
+use chrono_tz::Tz;
+
+use crate::model::{EventModel, EventStatus};
+use crate::note::NoteMeta;
+use crate::vault::SourcedEvent;
+
+// RFC 5545 3.1: content lines SHOULD NOT be longer than 75 octets, excluding the CRLF
+const MAX_LINE_OCTETS: usize = 75;
+const PRODID: &str = "-//md-event-parser//EN";
+
+const ICS_DATE: &str = "%Y%m%d";
+const ICS_DATETIME: &str = "%Y%m%dT%H%M%S";
+
+pub fn escape_text(text: &str) -> String {
+  /* RFC 5545 3.3.11, backslash has to go first so we don't double escape the others */
+  let mut ret = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '\\' => ret.push_str("\\\\"),
+      ';' => ret.push_str("\\;"),
+      ',' => ret.push_str("\\,"),
+      '\n' => ret.push_str("\\n"),
+      '\r' => {},
+      c => ret.push(c),
+    }
+  }
+  ret
+}
+
+pub fn fold_line(line: &str) -> String {
+  /* Split into 75 octet chunks without cutting a UTF-8 char in half, continuation lines start with a single space */
+  let mut ret = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+  let mut width = 0;
+  for c in line.chars() {
+    if width + c.len_utf8() > MAX_LINE_OCTETS {
+      ret.push_str("\r\n ");
+      width = 1;
+    }
+    ret.push(c);
+    width += c.len_utf8();
+  }
+  ret
+}
+
+fn write_line<W: Write>(out: &mut W, line: &str) -> io::Result<()> {
+  write!(out, "{}\r\n", fold_line(line))
+}
+
+fn write_event<W: Write>(out: &mut W, sourced: &SourcedEvent, dtstamp: NaiveDateTime) -> io::Result<()> {
+  let em = &sourced.event;
+  write_line(out, "BEGIN:VEVENT")?;
+  write_line(out, &format!("UID:{}", escape_text(em.uid())))?;
+  write_line(out, &format!("DTSTAMP:{}Z", dtstamp.format(ICS_DATETIME)))?;
+  if let Some(created) = sourced.note.created {
+    // CREATED has to be UTC, a note with no zone of its own is taken to be written in UTC.
+    // The event's zone says where the show is, not where the note was written
+    let created = sourced.note.timezone.unwrap_or(Tz::UTC)
+      .from_local_datetime(&created).earliest()
+      .map_or(created, |c| c.naive_utc());
+    write_line(out, &format!("CREATED:{}Z", created.format(ICS_DATETIME)))?;
+  }
+
+  match em.start_time() {
+    None => {
+      // All day, or a time nobody knows yet which is best shown on the day too.
+      // DTEND is exclusive so a single day event ends the day after it starts
+      let end = em.end_date().checked_add_days(Days::new(1)).unwrap_or(em.end_date());
+      write_line(out, &format!("DTSTART;VALUE=DATE:{}", em.start_date().format(ICS_DATE)))?;
+      write_line(out, &format!("DTEND;VALUE=DATE:{}", end.format(ICS_DATE)))?;
+    },
+    Some(_) if em.timezone().is_some() => {
+      // Zoned times go out as UTC, which every client understands without us shipping VTIMEZONE blocks
+      if let Some(start) = em.start_datetime() {
+        write_line(out, &format!("DTSTART:{}Z", start.naive_utc().format(ICS_DATETIME)))?;
+      }
+      if let Some(end) = em.end_datetime() {
+        write_line(out, &format!("DTEND:{}Z", end.naive_utc().format(ICS_DATETIME)))?;
+      }
+    },
+    Some(_) => {
+      // No zone anywhere, so these are floating times in whatever zone the calendar is in
+      if let Some(start) = em.start_naive() {
+        write_line(out, &format!("DTSTART:{}", start.format(ICS_DATETIME)))?;
+      }
+      if let Some(end) = em.end_naive() {
+        write_line(out, &format!("DTEND:{}", end.format(ICS_DATETIME)))?;
+      }
+    },
+  }
+
+  // Events get struck through once they've passed too, so only an open one that's crossed out is cancelled
+  let status = match em.status() {
+    EventStatus::Open if em.crossed_out() => EventStatus::Cancelled,
+    status => status,
+  };
+  write_line(out, &format!("STATUS:{}", status.ical_status()))?;
+  if em.status() == EventStatus::Important {
+    write_line(out, "PRIORITY:1")?;
+  }
+  write_line(out, &format!("SUMMARY:{}", escape_text(em.title())))?;
+  if !em.place().is_empty() {
+    write_line(out, &format!("LOCATION:{}", escape_text(em.place())))?;
+  }
+  let mut description = Vec::new();
+  if em.time_tba() {
+    description.push("Time TBA".to_owned());
+  }
+  if let (Some(doors), Some(show)) = (em.doors(), em.show()) {
+    description.push(format!("Doors {}, Show {}", doors.format("%-I:%M %p"), show.format("%-I:%M %p")));
+  }
+  if !description.is_empty() {
+    write_line(out, &format!("DESCRIPTION:{}", escape_text(&description.join("\n"))))?;
+  }
+  write_line(out, "END:VEVENT")
+}
+
+pub fn write_calendar<'a, W: Write>(out: &mut W, events: impl IntoIterator<Item = &'a SourcedEvent>) -> io::Result<()> {
+  let dtstamp = Utc::now().naive_utc();
+  write_line(out, "BEGIN:VCALENDAR")?;
+  write_line(out, "VERSION:2.0")?;
+  write_line(out, &format!("PRODID:{PRODID}"))?;
+  write_line(out, "CALSCALE:GREGORIAN")?;
+  for em in events {
+    write_event(out, em, dtstamp)?;
+  }
+  write_line(out, "END:VCALENDAR")
+}
+
+pub fn to_ics<'a>(events: impl IntoIterator<Item = &'a SourcedEvent>) -> String {
+  let mut buf = Vec::new();
+  write_calendar(&mut buf, events).expect("Writing to a Vec can't fail");
+  String::from_utf8(buf).expect("Everything we write is a str")
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::ical::*;
+  use crate::vault::tests::sourced;
+
+  fn event(line: &str) -> SourcedEvent {
+    sourced(EventModel::from_line(line.to_string()).unwrap())
+  }
+
+  #[test]
+  fn test_escape_text() {
+    assert_eq!(escape_text("White Oak Music Hall, Houston"), "White Oak Music Hall\\, Houston");
+    assert_eq!(escape_text("a;b\\c\nd"), "a\\;b\\\\c\\nd");
+  }
+
+  #[test]
+  fn test_fold_line() {
+    let short = "SUMMARY:Trapt";
+    assert_eq!(fold_line(short), short);
+
+    let long = format!("SUMMARY:{}", "é".repeat(60));
+    let folded = fold_line(&long);
+    for line in folded.split("\r\n") {
+      assert!(line.len() <= MAX_LINE_OCTETS);
+    }
+    assert_eq!(folded.replace("\r\n ", ""), long);
+  }
+
+  #[test]
+  fn test_all_day_event() {
+    let ics = to_ics([&event("- [ ] (24-25 Feb 2024) () () Excision")]);
+    assert!(ics.contains("DTSTART;VALUE=DATE:20240224\r\n"));
+    assert!(ics.contains("DTEND;VALUE=DATE:20240226\r\n"));
+    assert!(ics.contains("SUMMARY:Excision\r\n"));
+    assert!(!ics.contains("LOCATION"));
+    assert!(!ics.contains("CREATED"));
+  }
+
+  #[test]
+  fn test_created_from_note() {
+    let mut sourced = event("- [ ] (24-25 Feb 2024) () () Excision");
+    sourced.note = NoteMeta::parse(None, "202310152010\nTimezone: America/Chicago\n");
+    let ics = to_ics([&sourced]);
+    assert!(ics.contains("CREATED:20231016T011000Z\r\n"));
+
+    let mut sourced = event("- [ ] (24 Feb 2024) (7 PM America/Chicago) () Excision");
+    sourced.note = NoteMeta::parse(None, "202310152010\n");
+    let ics = to_ics([&sourced]);
+    assert!(ics.contains("CREATED:20231015T201000Z\r\n"));
+  }
+
+  #[test]
+  fn test_timed_event() {
+    let ics = to_ics([&event("- [ ] (15 Feb 2024) (6-10PM) (White Oak Music Hall, Houston) The Plot in You & Beartooth")]);
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    assert!(ics.contains("DTSTART:20240215T180000\r\n"));
+    assert!(ics.contains("DTEND:20240215T220000\r\n"));
+    assert!(ics.contains("LOCATION:White Oak Music Hall\\, Houston\r\n"));
+  }
+
+  #[test]
+  fn test_named_times() {
+    let ics = to_ics([&event("- [ ] (15 Feb 2024) (TBA) () Secret Show")]);
+    assert!(ics.contains("DTSTART;VALUE=DATE:20240215\r\n"));
+    assert!(ics.contains("DESCRIPTION:Time TBA\r\n"));
+
+    let ics = to_ics([&event("- [ ] (15 Feb 2024) (Doors 6PM, Show 7PM) () The Plot in You")]);
+    assert!(ics.contains("DTSTART:20240215T180000\r\n"));
+    assert!(ics.contains("DESCRIPTION:Doors 6:00 PM\\, Show 7:00 PM\r\n"));
+  }
+
+  #[test]
+  fn test_status() {
+    let ics = to_ics([&event("- [ ] (15 Feb 2024) () () Open")]);
+    assert!(ics.contains("STATUS:CONFIRMED\r\n"));
+    assert!(!ics.contains("PRIORITY"));
+    let ics = to_ics([&event("- [-] (15 Feb 2024) () () Cancelled")]);
+    assert!(ics.contains("STATUS:CANCELLED\r\n"));
+    let ics = to_ics([&event("- [?] (15 Feb 2024) () () Maybe")]);
+    assert!(ics.contains("STATUS:TENTATIVE\r\n"));
+    let ics = to_ics([&event("- [!] (15 Feb 2024) () () Important")]);
+    assert!(ics.contains("STATUS:CONFIRMED\r\nPRIORITY:1\r\n"));
+    let ics = to_ics([&event("- [x] (15 Feb 2024) () () ~~Seen it~~")]);
+    assert!(ics.contains("STATUS:CONFIRMED\r\nSUMMARY:Seen it\r\n"));
+    let ics = to_ics([&event("- [ ] (15 Feb 2024) () () ~~Called off~~")]);
+    assert!(ics.contains("STATUS:CANCELLED\r\nSUMMARY:Called off\r\n"));
+  }
+
+  #[test]
+  fn test_zoned_event() {
+    let ics = to_ics([&event("- [ ] (15 Jul 2024) (6-10PM CST) (White Oak Music Hall, Houston) Summer Show")]);
+    assert!(ics.contains("DTSTART:20240715T230000Z\r\n"));
+    assert!(ics.contains("DTEND:20240716T030000Z\r\n"));
+  }
+}
@@ -0,0 +1,146 @@
+use std::path::Path;
+use std::sync::LazyLock;
+
+use chrono::{Duration, NaiveTime};
+use regex::Regex;
+
+use crate::config::ParseConfig;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::markdown;
+use crate::model::EventModel;
+use crate::note::NoteMeta;
+use crate::parsing;
+
+// A checkbox followed by a paren, close enough to EVENTREGEX that it was almost certainly meant to be an event
+pub const NEARMISSREGEX: &str = r"^(?:[ \t]*>)*[ \t]*(?:[-*+]|\d{1,9}[.)]) ?\[[ xX\->?!]?\] *(?:~~)?\(";
+// One paren group after the checkbox, a near miss is only an error with two of them or a date in the first
+pub const PARENGROUPREG: &str = r"\(([^()]*)\)";
+
+// Overnight ranges without a `+1` longer than this get flagged
+const MAX_IMPLICIT_OVERNIGHT_HOURS: i64 = 12;
+
+pub fn lint_note(path: &Path, filestr: &str, config: &ParseConfig) -> Vec<Diagnostic> {
+  static NEAR_MISS_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(NEARMISSREGEX).unwrap());
+  static PAREN_GROUP_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(PARENGROUPREG).unwrap());
+  let near_miss = &*NEAR_MISS_REG;
+  let config = config.for_note(&NoteMeta::parse(Some(path), filestr));
+  let mut ret = Vec::new();
+
+  for (line, linestr) in markdown::content_lines(filestr) {
+    let diag = |rule, message: String, span, help: &str| Diagnostic {
+      severity: Severity::Error,
+      rule,
+      message,
+      path: path.to_path_buf(),
+      line,
+      text: linestr.to_string(),
+      span: Some(span),
+      help: Some(help.to_owned()),
+    };
+
+    if !parsing::line_is_event(linestr) {
+      if let Some(mat) = near_miss.find(linestr) {
+        let span = (mat.end() - 1)..linestr.trim_end().len();
+        // `- [ ] (maybe) buy merch` is just a to-do, so a lone group that isn't a date is only worth a warning
+        let mut groups = PAREN_GROUP_REG.captures_iter(&linestr[span.start..]);
+        let dated = groups.next().is_some_and(|caps| EventModel::is_date_with(caps[1].trim(), &config));
+        let severity = match dated || groups.next().is_some() {
+          true => Severity::Error,
+          false => Severity::Warning,
+        };
+        ret.push(Diagnostic {
+          severity,
+          ..diag("near-miss", "line looks like an event but doesn't match `(date) (time) (place) title`".to_owned(), span,
+            "every event needs all three groups, leave the ones you don't know empty with `()`")
+        });
+      }
+      continue;
+    }
+
+    let em = match EventModel::from_line_with(linestr.to_string(), &config) {
+      Ok(em) => em,
+      Err(e) => {
+        ret.push(Diagnostic::from_error(path, line, linestr, &e.with_line(line)));
+        continue;
+      },
+    };
+    let [date_span, time_span, _, _] = EventModel::locate_groups(linestr).expect("from_line already matched the groups");
+
+    if em.end_date() < em.start_date() {
+      ret.push(diag("end-date-before-start", format!("event ends ({}) before it starts ({})", em.end_date(), em.start_date()), date_span,
+        "the second date should be on or after the first one"));
+    } else if let (Some(start), Some(end)) = (em.start_naive(), em.end_naive()) {
+      // Short overnight shows are fine, and so is anything ending at midnight. A guessed rollover this long is much
+      // more likely a mixed up AM/PM
+      if em.implicit_rollover() && end.time() != NaiveTime::MIN && end - start > Duration::hours(MAX_IMPLICIT_OVERNIGHT_HOURS) {
+        ret.push(diag("end-time-before-start", format!("event ends ({}) before it starts ({})", end.format("%-I:%M %p"), start.format("%-I:%M %p")), time_span,
+          "check the AM/PM on both times, or add `+1` if it really runs into the next day"));
+      }
+    }
+  }
+  ret
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::lint::*;
+
+  fn lint(filestr: &str) -> Vec<Diagnostic> {
+    lint_note(Path::new("Concerts.md"), filestr, &ParseConfig::default())
+  }
+
+  #[test]
+  fn test_lint_clean() {
+    let filestr = std::fs::read_to_string("res/example.md").unwrap();
+    assert!(lint(&filestr).is_empty());
+  }
+
+  #[test]
+  fn test_lint_parse_failure() {
+    let diags = lint("Tags: #event\n- [ ] (15 Feb 2024) (6ish) () The Plot in You\n");
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].line, 2);
+    assert_eq!(diags[0].message, "invalid time `6ish`");
+    assert_eq!(diags[0].rule, "bad-time");
+    assert_eq!(diags[0].severity, Severity::Error);
+  }
+
+  #[test]
+  fn test_lint_near_miss() {
+    let diags = lint("- [ ] (15 Feb 2024) (White Oak) The Plot in You\n- [ ] \n- [x](2 Nov) Polyphia\n");
+    assert_eq!(diags.iter().map(|d| d.line).collect::<Vec<_>>(), vec![1, 3]);
+    assert_eq!(diags[0].rule, "near-miss");
+    assert_eq!(diags[0].span, Some(6..47));
+    assert_eq!(diags[1].span, Some(5..21));
+    assert!(diags.iter().all(|d| d.severity == Severity::Error));
+
+    // A to-do with a single paren group that isn't a date is only a warning
+    let diags = lint("- [ ] (maybe) buy merch\n- [x](notes.md)\n");
+    assert_eq!(diags.iter().map(|d| (d.line, d.severity)).collect::<Vec<_>>(), vec![(1, Severity::Warning), (2, Severity::Warning)]);
+
+    // Other markers and quotes get the same treatment
+    let diags = lint("> 1. [ ] (15 Feb 2024) (White Oak) The Plot in You\n");
+    assert_eq!(diags[0].rule, "near-miss");
+    assert_eq!(diags[0].span, Some(9..50));
+
+    // Nothing in code or comments is checked, and lines after them keep their numbers
+    let diags = lint("```\n- [ ] (15 Feb 2024) (White Oak) Example\n```\n%% - [x](2 Nov) Polyphia %%\n- [x](2 Nov) Polyphia\n");
+    assert_eq!(diags.iter().map(|d| d.line).collect::<Vec<_>>(), vec![5]);
+  }
+
+  #[test]
+  fn test_lint_end_before_start() {
+    let diags = lint("- [ ] (28 Feb - 2 Jan 2024) () () Tour\n- [ ] (2 Nov 2023) (10PM-6PM) () Polyphia\n- [ ] (2-3 Nov 2023) (10PM-6PM) () Fest\n\
+      - [ ] (2 Nov 2023) (10PM-2AM) () Late\n- [ ] (2 Nov 2023) (10PM-6PM +1) () Long\n\
+      - [ ] (2 Nov 2023) (10pm-midnight) () Midnight\n- [ ] (2 Nov 2023) (8PM-12AM) () Midnight\n- [ ] (2 Nov 2023) (10AM-12AM) () Day\n");
+    let found: Vec<(usize, &str, Severity)> = diags.iter().map(|d| (d.line, d.rule, d.severity)).collect();
+    assert_eq!(found, vec![
+      (1, "end-date-before-start", Severity::Error),
+      (2, "end-time-before-start", Severity::Error),
+    ]);
+    assert_eq!(diags[0].message, "event ends (2024-01-02) before it starts (2024-02-28)");
+    assert_eq!(diags[0].span, Some(7..26));
+    assert_eq!(diags[1].message, "event ends (6:00 PM) before it starts (10:00 PM)");
+    assert_eq!(diags[1].span, Some(20..28));
+  }
+}
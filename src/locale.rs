@@ -0,0 +1,186 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+
+// Which language month and weekday names are read and written in. English is always understood as well,
+// so a German note can still say `Nov` or `Oct` and be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+  #[default]
+  En,
+  Es,
+  De,
+  Fr,
+  Pt,
+}
+
+// The first name of each entry is the one we write, the rest are also accepted. Compared without case or a trailing `.`
+type Names = [&'static [&'static str]];
+
+const EN_MONTHS: [&[&str]; 12] = [
+  &["Jan", "January"], &["Feb", "February"], &["Mar", "March"], &["Apr", "April"], &["May"], &["Jun", "June"],
+  &["Jul", "July"], &["Aug", "August"], &["Sep", "September", "Sept"], &["Oct", "October"], &["Nov", "November"], &["Dec", "December"],
+];
+const ES_MONTHS: [&[&str]; 12] = [
+  &["ene", "enero"], &["feb", "febrero"], &["mar", "marzo"], &["abr", "abril"], &["may", "mayo"], &["jun", "junio"],
+  &["jul", "julio"], &["ago", "agosto"], &["sept", "septiembre", "setiembre", "sep", "set"], &["oct", "octubre"], &["nov", "noviembre"], &["dic", "diciembre"],
+];
+const DE_MONTHS: [&[&str]; 12] = [
+  &["Jan", "Januar", "Jänner", "Jän"], &["Feb", "Februar"], &["Mär", "März", "Maerz", "Mrz"], &["Apr", "April"], &["Mai"], &["Jun", "Juni"],
+  &["Jul", "Juli"], &["Aug", "August"], &["Sep", "September", "Sept"], &["Okt", "Oktober"], &["Nov", "November"], &["Dez", "Dezember"],
+];
+const FR_MONTHS: [&[&str]; 12] = [
+  &["janv.", "janvier", "jan"], &["févr.", "février", "fevrier", "fevr", "fév", "fev"], &["mars", "mar"], &["avr.", "avril"], &["mai"], &["juin"],
+  &["juil.", "juillet"], &["août", "aout"], &["sept.", "septembre"], &["oct.", "octobre"], &["nov.", "novembre"], &["déc.", "décembre", "decembre", "dec"],
+];
+const PT_MONTHS: [&[&str]; 12] = [
+  &["jan", "janeiro"], &["fev", "fevereiro"], &["mar", "março", "marco"], &["abr", "abril"], &["mai", "maio"], &["jun", "junho"],
+  &["jul", "julho"], &["ago", "agosto"], &["set", "setembro"], &["out", "outubro"], &["nov", "novembro"], &["dez", "dezembro"],
+];
+
+// Monday first, like chrono's num_days_from_monday
+const EN_WEEKDAYS: [&[&str]; 7] = [
+  &["Mon", "Monday"], &["Tue", "Tuesday", "Tues"], &["Wed", "Wednesday"], &["Thu", "Thursday", "Thur", "Thurs"],
+  &["Fri", "Friday"], &["Sat", "Saturday"], &["Sun", "Sunday"],
+];
+const ES_WEEKDAYS: [&[&str]; 7] = [
+  &["lun", "lunes"], &["mar", "martes"], &["mié", "miércoles", "mie", "miercoles"], &["jue", "jueves"],
+  &["vie", "viernes"], &["sáb", "sábado", "sab", "sabado"], &["dom", "domingo"],
+];
+const DE_WEEKDAYS: [&[&str]; 7] = [
+  &["Mo", "Montag"], &["Di", "Dienstag"], &["Mi", "Mittwoch"], &["Do", "Donnerstag"],
+  &["Fr", "Freitag"], &["Sa", "Samstag", "Sonnabend"], &["So", "Sonntag"],
+];
+const FR_WEEKDAYS: [&[&str]; 7] = [
+  &["lun.", "lundi"], &["mar.", "mardi"], &["mer.", "mercredi"], &["jeu.", "jeudi"],
+  &["ven.", "vendredi"], &["sam.", "samedi"], &["dim.", "dimanche"],
+];
+const PT_WEEKDAYS: [&[&str]; 7] = [
+  &["seg", "segunda", "segunda-feira"], &["ter", "terça", "terça-feira", "terca"], &["qua", "quarta", "quarta-feira"], &["qui", "quinta", "quinta-feira"],
+  &["sex", "sexta", "sexta-feira"], &["sáb", "sábado", "sab", "sabado"], &["dom", "domingo"],
+];
+
+fn lookup(names: &Names, word: &str) -> Option<usize> {
+  let word = word.trim_end_matches('.').to_lowercase();
+  names.iter().position(|entry| entry.iter().any(|name| name.trim_end_matches('.').to_lowercase() == word))
+}
+
+impl Locale {
+  fn months(self) -> &'static Names {
+    match self {
+      Locale::En => &EN_MONTHS,
+      Locale::Es => &ES_MONTHS,
+      Locale::De => &DE_MONTHS,
+      Locale::Fr => &FR_MONTHS,
+      Locale::Pt => &PT_MONTHS,
+    }
+  }
+
+  fn weekdays(self) -> &'static Names {
+    match self {
+      Locale::En => &EN_WEEKDAYS,
+      Locale::Es => &ES_WEEKDAYS,
+      Locale::De => &DE_WEEKDAYS,
+      Locale::Fr => &FR_WEEKDAYS,
+      Locale::Pt => &PT_WEEKDAYS,
+    }
+  }
+
+  pub fn month(self, name: &str) -> Option<u32> {
+    /* 1-based, this locale's names first then English */
+    lookup(self.months(), name)
+      .or_else(|| lookup(Locale::En.months(), name))
+      .map(|i| i as u32 + 1)
+  }
+
+  pub fn weekday(self, name: &str) -> Option<Weekday> {
+    lookup(self.weekdays(), name)
+      .or_else(|| lookup(Locale::En.weekdays(), name))
+      .and_then(|i| Weekday::try_from(i as u8).ok())
+  }
+
+  pub fn month_abbr(self, month: u32) -> &'static str {
+    self.months()[month as usize - 1][0]
+  }
+
+  pub fn weekday_abbr(self, weekday: Weekday) -> &'static str {
+    self.weekdays()[weekday.num_days_from_monday() as usize][0]
+  }
+
+  pub fn format_date(self, date: NaiveDate) -> String {
+    /* `Sat 15 Feb 2024`, `Sa 15 Feb 2024`, `sáb 15 feb 2024`, the same layout as DATEFMT with the names swapped */
+    format!("{} {} {} {}", self.weekday_abbr(date.weekday()), date.day(), self.month_abbr(date.month()), date.year())
+  }
+
+  pub fn format_time(self, time: NaiveTime) -> String {
+    /* The 12 hour clock is an English thing, everyone else gets 24 hours */
+    match self {
+      Locale::En => time.format("%-I:%M %p").to_string(),
+      _ => time.format("%H:%M").to_string(),
+    }
+  }
+}
+
+impl std::str::FromStr for Locale {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Locale, String> {
+    /* Only the language matters, so `de-AT` and `pt_BR` are fine */
+    let lang = s.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+    match lang.as_str() {
+      "en" => Ok(Locale::En),
+      "es" => Ok(Locale::Es),
+      "de" => Ok(Locale::De),
+      "fr" => Ok(Locale::Fr),
+      "pt" => Ok(Locale::Pt),
+      _ => Err(format!("unknown locale {s}, expected en, es, de, fr or pt")),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::locale::*;
+
+  #[test]
+  fn test_month() {
+    assert_eq!(Locale::De.month("März"), Some(3));
+    assert_eq!(Locale::De.month("mär"), Some(3));
+    assert_eq!(Locale::Es.month("noviembre"), Some(11));
+    assert_eq!(Locale::Es.month("feb"), Some(2));
+    assert_eq!(Locale::Fr.month("févr."), Some(2));
+    assert_eq!(Locale::Pt.month("Setembro"), Some(9));
+    // English always works, other locales only when asked for
+    assert_eq!(Locale::De.month("October"), Some(10));
+    assert_eq!(Locale::En.month("März"), None);
+    assert_eq!(Locale::Fr.month("Smarch"), None);
+  }
+
+  #[test]
+  fn test_weekday() {
+    assert_eq!(Locale::De.weekday("Samstag"), Some(Weekday::Sat));
+    assert_eq!(Locale::Es.weekday("miércoles"), Some(Weekday::Wed));
+    assert_eq!(Locale::Fr.weekday("dim."), Some(Weekday::Sun));
+    assert_eq!(Locale::Pt.weekday("sexta-feira"), Some(Weekday::Fri));
+    assert_eq!(Locale::En.weekday("Thurs"), Some(Weekday::Thu));
+  }
+
+  #[test]
+  fn test_format_date() {
+    let date = NaiveDate::from_ymd_opt(2024, 2, 24).unwrap();
+    assert_eq!(Locale::En.format_date(date), "Sat 24 Feb 2024");
+    assert_eq!(Locale::De.format_date(date), "Sa 24 Feb 2024");
+    assert_eq!(Locale::Es.format_date(date), "sáb 24 feb 2024");
+    assert_eq!(Locale::Fr.format_date(date), "sam. 24 févr. 2024");
+
+    let time = NaiveTime::from_hms_opt(18, 30, 0).unwrap();
+    assert_eq!(Locale::En.format_time(time), "6:30 PM");
+    assert_eq!(Locale::De.format_time(time), "18:30");
+  }
+
+  #[test]
+  fn test_from_str() {
+    assert_eq!("de".parse(), Ok(Locale::De));
+    assert_eq!("pt-BR".parse(), Ok(Locale::Pt));
+    assert_eq!("ES".parse(), Ok(Locale::Es));
+    assert!("it".parse::<Locale>().is_err());
+  }
+}
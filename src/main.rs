@@ -3,20 +3,246 @@
 use std::env;
 use std::{fs, fs::File};
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 
 use regex::Regex;
 
+mod config;
+use config::ParseConfig;
+
+mod locale;
+
+mod note;
+
+mod markdown;
+
 mod model;
 use model::EventModel;
 
 mod parsing;
-use parsing::{file_is_event, line_is_event};
+use parsing::line_is_event;
+
+mod vault;
+use vault::{VaultScan, VaultScanner};
+
+mod ical;
+
+mod agenda;
+
+mod diagnostic;
+use diagnostic::{Diagnostic, Severity};
+
+mod lint;
+
+mod report;
+use report::ReportFormat;
 // use crate::lib::prelude::*;
 
+const USAGE: &str = "\
+usage: md-event-parser parse [--format json|ics|text] [--skip-crossed-out] [options] <file.md|vault dir>...
+       md-event-parser lint [--warn-only] [options] <file.md|vault dir>...
+options: [--tz <zone>] [--year next|nearest|note|<year>] [--reference-date <YYYY-MM-DD>]
+         [--pivot <yy>] [--date-order month-first|day-first] [--locale en|es|de|fr|pt]
+         [--tag <name>] [--diagnostics text|github|sarif|json] [--color auto|always|never] [--include <glob>]... [--exclude <glob>]...";
+
+#[derive(Debug, Default)]
+struct Cli {
+  paths: Vec<PathBuf>,
+  include: Vec<String>,
+  exclude: Vec<String>,
+  format: Option<String>,
+  color: Option<String>,
+  diagnostics: ReportFormat,
+  warn_only: bool,
+  skip_crossed_out: bool,
+  config: ParseConfig,
+}
+
+impl Cli {
+  fn parse(args: &[String]) -> Result<Cli, String> {
+    let mut cli = Cli::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "--include" => cli.include.push(args.next().ok_or("--include needs a glob")?.clone()),
+        "--exclude" => cli.exclude.push(args.next().ok_or("--exclude needs a glob")?.clone()),
+        "--format" => cli.format = Some(args.next().ok_or("--format needs a value")?.clone()),
+        "--color" => cli.color = Some(args.next().ok_or("--color needs a value")?.clone()),
+        "--diagnostics" => cli.diagnostics = args.next().ok_or("--diagnostics needs a value")?.parse()?,
+        "--warn-only" => cli.warn_only = true,
+        "--skip-crossed-out" => cli.skip_crossed_out = true,
+        "--tz" => {
+          let name = args.next().ok_or("--tz needs a zone")?;
+          cli.config.default_tz = Some(config::parse_tz(name).ok_or(format!("unknown timezone {name}"))?);
+        },
+        "--pivot" => {
+          let pivot = args.next().ok_or("--pivot needs a two digit year")?;
+          match pivot.parse() {
+            Ok(p @ 0..=100) => cli.config.two_digit_pivot = Some(p),
+            _ => return Err(format!("bad --pivot {pivot}, expected 0 to 100")),
+          }
+        },
+        "--tag" => {
+          let tag = args.next().ok_or("--tag needs a tag name")?.trim_start_matches('#');
+          if tag.is_empty() {
+            return Err("--tag needs a tag name".to_owned());
+          }
+          cli.config.event_tag = Some(tag.to_owned());
+        },
+        "--locale" => cli.config.locale = args.next().ok_or("--locale needs a language")?.parse()?,
+        "--date-order" => cli.config.date_order = args.next().ok_or("--date-order needs a value")?.parse()?,
+        "--year" => cli.config.year_policy = args.next().ok_or("--year needs a policy")?.parse()?,
+        "--reference-date" => {
+          let date = args.next().ok_or("--reference-date needs a date")?;
+          cli.config.reference_date = Some(date.parse().map_err(|e| format!("bad --reference-date {date}: {e}"))?);
+        },
+        flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+        path => cli.paths.push(PathBuf::from(path)),
+      }
+    }
+    if cli.paths.is_empty() {
+      return Err("no files given".to_owned());
+    }
+    Ok(cli)
+  }
+
+  fn scanner(&self) -> Result<VaultScanner, String> {
+    let mut scanner = VaultScanner::new().config(self.config.clone());
+    for pat in &self.include {
+      scanner = scanner.include(pat).map_err(|e| format!("bad --include glob {pat}: {e}"))?;
+    }
+    for pat in &self.exclude {
+      scanner = scanner.exclude(pat).map_err(|e| format!("bad --exclude glob {pat}: {e}"))?;
+    }
+    Ok(scanner)
+  }
+
+  fn notes(&self) -> Result<(Vec<(PathBuf, PathBuf)>, bool), String> {
+    /*
+     * Directories are walked as vaults, plain files are taken as single notes whose vault is the folder they're in,
+     * so a lone note's uids are namespaced by just its file name. Returns (vault root, note) pairs and whether
+     * every path could be read.
+     */
+    let scanner = self.scanner()?;
+    let mut ret = Vec::new();
+    let mut all_read = true;
+    for path in &self.paths {
+      if path.is_dir() {
+        match scanner.notes(path) {
+          Ok(notes) => ret.extend(notes.into_iter().map(|note| (path.clone(), note))),
+          Err(why) => {
+            eprintln!("couldn't read {}: {}", path.display(), why);
+            all_read = false;
+          },
+        }
+      } else {
+        ret.push((path.parent().unwrap_or(Path::new("")).to_path_buf(), path.clone()));
+      }
+    }
+    Ok((ret, all_read))
+  }
+
+  fn scan(&self) -> Result<(VaultScan, bool), String> {
+    /* Same split as notes(), but vaults go through VaultScanner::scan so a bad note doesn't stop the rest */
+    let scanner = self.scanner()?;
+    let mut ret = VaultScan::default();
+    let mut all_read = true;
+    for path in &self.paths {
+      if path.is_dir() {
+        match scanner.scan(path) {
+          Ok(scan) => ret.extend(scan),
+          Err(why) => {
+            eprintln!("couldn't read {}: {}", path.display(), why);
+            all_read = false;
+          },
+        }
+      } else {
+        ret.add_note(path.parent().unwrap_or(Path::new("")), path, &self.config);
+      }
+    }
+    for (path, why) in &ret.unreadable {
+      eprintln!("couldn't read {}: {}", path.display(), why);
+    }
+    all_read &= ret.unreadable.is_empty();
+    Ok((ret, all_read))
+  }
+}
 
-fn main() {
+fn main() -> ExitCode {
   use crate::*;
-  
 
+  let args: Vec<String> = env::args().skip(1).collect();
+  let res = match args.first().map(String::as_str) {
+    Some("parse") => Cli::parse(&args[1..]).and_then(|cli| parse_cmd(&cli)),
+    Some("lint") => Cli::parse(&args[1..]).and_then(|cli| lint_cmd(&cli)),
+    _ => Err("no command given".to_owned()),
+  };
+  match res {
+    Ok(code) => code,
+    Err(e) => {
+      eprintln!("{e}\n{USAGE}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn parse_cmd(cli: &Cli) -> Result<ExitCode, String> {
+  /* Events go to stdout as one JSON array, anything that looked like an event but didn't parse goes to stderr */
+  let color = diagnostic::use_color(cli.color.as_deref())?;
+  let (mut scan, all_read) = cli.scan()?;
+  if cli.skip_crossed_out {
+    scan.events.retain(|s| !s.event.crossed_out());
+  }
+
+  // stdout is taken by the events, so failures go to stderr whatever the format
+  let diags: Vec<Diagnostic> = scan.failures.iter().map(Diagnostic::from_failure).collect();
+  report::write_report(&mut std::io::stderr(), cli.diagnostics, &diags, color).map_err(|e| e.to_string())?;
+
+  match cli.format.as_deref().unwrap_or("json") {
+    "json" => {
+      let json = serde_json::to_string_pretty(&scan.events).map_err(|e| format!("couldn't serialize events: {e}"))?;
+      println!("{json}");
+    },
+    "ics" => print!("{}", ical::to_ics(&scan.events)),
+    "text" => agenda::write_agenda(&mut std::io::stdout(), &scan.events).map_err(|e| e.to_string())?,
+    other => return Err(format!("unknown format {other}, expected json, ics or text")),
   }
+
+  Ok(if all_read { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
+
+fn lint_cmd(cli: &Cli) -> Result<ExitCode, String> {
+  /* Fails on any error so it can gate commits, --warn-only reports everything as a warning and always passes */
+  let color = diagnostic::use_color(cli.color.as_deref())?;
+  let (notes, mut all_read) = cli.notes()?;
+
+  let mut diags = Vec::new();
+  for (_, path) in &notes {
+    match vault::read_tagged(path, &cli.config) {
+      Ok(Some((filestr, _))) => diags.extend(lint::lint_note(path, &filestr, &cli.config)),
+      Ok(None) => {},
+      Err(why) => {
+        eprintln!("couldn't read {}: {}", path.display(), why);
+        all_read = false;
+      },
+    }
+  }
+
+  if cli.warn_only {
+    diags.iter_mut().for_each(|d| d.severity = Severity::Warning);
+  }
+  // Human output goes to stderr like a compiler's, machine formats to stdout so they can be piped or saved
+  let res = match cli.diagnostics {
+    ReportFormat::Text => report::write_report(&mut std::io::stderr(), cli.diagnostics, &diags, color),
+    _ => report::write_report(&mut std::io::stdout(), cli.diagnostics, &diags, color),
+  };
+  res.map_err(|e| e.to_string())?;
+
+  let errors = diags.iter().filter(|d| d.severity == Severity::Error).count();
+  let warnings = diags.len() - errors;
+  eprintln!("lint: {errors} error(s), {warnings} warning(s) in {} note(s)", notes.len());
+
+  Ok(if errors == 0 && all_read { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
@@ -0,0 +1,191 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::parsing;
+
+// Opening code fence, three or more backticks or tildes indented by at most three spaces
+pub const FENCEREG: &str = r"^ {0,3}(`{3,}|~{3,})";
+// Everything in front of a blockquote's content, fences and indented code work the same inside quotes
+pub const QUOTEPREFIXREG: &str = r"^(?:[ \t]*>)*";
+
+const CODE_INDENT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+  Text,
+  Frontmatter,
+  Fence { marker: char, len: usize, quotes: usize }, // A fence inside a blockquote ends with the quote
+  IndentedCode,
+  HtmlComment, // `<!-- -->`
+  ObsidianComment, // `%% %%`
+}
+
+fn indent_width(linestr: &str) -> usize {
+  linestr.chars()
+    .take_while(|c| *c == ' ' || *c == '\t')
+    .map(|c| if c == '\t' { CODE_INDENT } else { 1 })
+    .sum()
+}
+
+fn code_span_end(rest: &str) -> usize {
+  /* Just past the code span `rest` starts with, or past its opening backticks if nothing closes it */
+  let ticks = rest.len() - rest.trim_start_matches('`').len();
+  let mut from = ticks;
+  while let Some(i) = rest[from..].find('`') {
+    let start = from + i;
+    let run = rest[start..].len() - rest[start..].trim_start_matches('`').len();
+    if run == ticks {
+      return start + run;
+    }
+    from = start + run;
+  }
+  ticks
+}
+
+fn comments_after(linestr: &str, mut state: State) -> State {
+  /*
+   * Where a line leaves us with respect to comments, they can open and close any number of times on one line.
+   * `%%` or `<!--` inside a code span is just text, like in ``Use `%%` for comments``.
+   */
+  let mut rest = linestr;
+  loop {
+    let (found, next) = match state {
+      State::Text => {
+        let opener = [("`", State::Text), ("%%", State::ObsidianComment), ("<!--", State::HtmlComment)].into_iter()
+          .filter_map(|(pat, next)| rest.find(pat).map(|i| (i, pat, next)))
+          .min_by_key(|(i, _, _)| *i);
+        match opener {
+          Some((i, "`", _)) => (Some(i + code_span_end(&rest[i..])), State::Text),
+          Some((i, pat, next)) => (Some(i + pat.len()), next),
+          None => (None, state),
+        }
+      },
+      State::ObsidianComment => (rest.find("%%").map(|i| i + 2), State::Text),
+      State::HtmlComment => (rest.find("-->").map(|i| i + 3), State::Text),
+      _ => (None, state),
+    };
+    match found {
+      Some(end) => {
+        rest = &rest[end..];
+        state = next;
+      },
+      None => return state,
+    }
+  }
+}
+
+pub fn content_lines(filestr: &str) -> Vec<(usize, &str)> {
+  /*
+   * The lines of a note that Obsidian would render as text, with their 1-based line numbers. Leaves out YAML
+   * frontmatter, fenced and indented code, and lines that start inside an HTML or `%%` comment. A comment that
+   * opens partway through a line keeps that line, anything written before it is still text.
+   */
+  static FENCE_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(FENCEREG).unwrap());
+  static QUOTE_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(QUOTEPREFIXREG).unwrap());
+  let (fence_reg, quote_reg) = (&*FENCE_REG, &*QUOTE_REG);
+
+  let mut ret = Vec::new();
+  let mut state = State::Text;
+  let mut prev_blank = true;
+  let mut list_open = false;
+
+  for (idx, linestr) in filestr.lines().enumerate() {
+    let prefix = quote_reg.find(linestr).map_or("", |m| m.as_str());
+    let body = &linestr[prefix.len()..];
+    let quotes = prefix.matches('>').count();
+    let blank = body.trim().is_empty();
+
+    match state {
+      State::Frontmatter => {
+        if matches!(linestr.trim_end(), "---" | "...") {
+          state = State::Text;
+        }
+        continue;
+      },
+      // Leaving the quote a fence was opened in closes it, and this line is back to normal text
+      State::Fence { quotes: fence_quotes, .. } if quotes < fence_quotes => state = State::Text,
+      State::Fence { marker, len, .. } => {
+        let trimmed = body.trim();
+        if indent_width(body) < CODE_INDENT && trimmed.len() >= len && trimmed.chars().all(|c| c == marker) {
+          state = State::Text;
+        }
+        continue;
+      },
+      State::HtmlComment | State::ObsidianComment => {
+        state = comments_after(linestr, state);
+        continue;
+      },
+      State::IndentedCode if blank || indent_width(body) >= CODE_INDENT => continue,
+      State::IndentedCode | State::Text => state = State::Text,
+    }
+
+    if idx == 0 && linestr.trim_end() == "---" {
+      state = State::Frontmatter;
+      continue;
+    }
+    if let Some(caps) = fence_reg.captures(body) {
+      state = State::Fence { marker: caps[1].chars().next().unwrap(), len: caps[1].len(), quotes };
+      prev_blank = false;
+      continue;
+    }
+    // Indented code can't interrupt a paragraph, and inside a list the indent belongs to the list
+    if !blank && prev_blank && !list_open && indent_width(body) >= CODE_INDENT {
+      state = State::IndentedCode;
+      continue;
+    }
+
+    ret.push((idx + 1, linestr));
+    if parsing::list_prefix(linestr).is_some() {
+      list_open = true;
+    } else if !blank && indent_width(body) == 0 {
+      list_open = false;
+    }
+    prev_blank = blank;
+    state = comments_after(linestr, state);
+  }
+  ret
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::markdown::*;
+
+  fn numbers(filestr: &str) -> Vec<usize> {
+    content_lines(filestr).into_iter().map(|(line, _)| line).collect()
+  }
+
+  #[test]
+  fn test_frontmatter() {
+    assert_eq!(numbers("---\ntags: [event]\n- [ ] (2 Nov) () () Hidden\n---\nText\n"), vec![5]);
+    // Only on the first line, later it's a thematic break
+    assert_eq!(numbers("Text\n---\nMore\n"), vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn test_fences() {
+    let note = "a\n```md\n- [ ] (2 Nov) () () Example\n~~~\n```\nb\n~~~~\n```\n~~~~\nc\n> ```\n> - [ ] (2 Nov) () () Quoted\n> ```\nd\n";
+    assert_eq!(numbers(note), vec![1, 6, 10, 14]);
+    // An unclosed fence in a quote ends with the quote
+    assert_eq!(numbers("> ```\n> - [ ] (2 Nov) () () Quoted\n\n- [ ] (15 Feb 2024) () () After\n"), vec![3, 4]);
+    assert_eq!(numbers("> ```\n>\n> > nested\n> ```\nafter\n"), vec![5]);
+  }
+
+  #[test]
+  fn test_indented_code() {
+    assert_eq!(numbers("Text\n\n    - [ ] (2 Nov) () () Code\n\tmore code\n\nback\n"), vec![1, 2, 6]);
+    // Not after a paragraph line, and not under a list item
+    assert_eq!(numbers("Text\n    continued\n"), vec![1, 2]);
+    assert_eq!(numbers("- Tour\n\n    - [ ] (2 Nov) () () Nested\n"), vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn test_comments() {
+    let note = "a %% hidden\n- [ ] (2 Nov) () () Hidden\nstill %% b\n%% one line %%\n<!--\n- [ ] (2 Nov) () () Hidden\n-->\nc <!-- x --> d %%\n%%\ne\n";
+    assert_eq!(numbers(note), vec![1, 4, 5, 8, 10]);
+
+    // Openers in code spans are text, an unmatched backtick is just a backtick
+    let note = "Use `%%` or ``<!-- ` --> `` for comments\n- [ ] (2 Nov) () () Shown\nstray ` %% hidden\n- [ ] (2 Nov) () () Hidden\n%%\n";
+    assert_eq!(numbers(note), vec![1, 2, 3]);
+  }
+}
@@ -1,20 +1,140 @@
 #![allow(unused)]
 
-use chrono::{Datelike, NaiveDate, NaiveTime, ParseResult, Utc};
-use std::{error::Error, fmt, io::BufRead};
+use chrono::{DateTime, Datelike, Days, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, ParseResult, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::{error::Error, fmt, io::BufRead, ops::Range, sync::LazyLock};
 use serde::{Serialize, Deserialize};
 use regex::Regex;
 
+use crate::config::{self, DateOrder, ParseConfig};
+use crate::locale::Locale;
 use crate::parsing;
 
-#[derive(Debug, Clone, Default)]
-pub struct EventParseError {
-  desc: String
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+  pub line: Option<usize>, // 1-based line in the note, only known once something above from_line fills it in
+  pub span: Option<Range<usize>>, // Byte range of the offending group inside the line, parens excluded
+}
+
+impl Location {
+  pub fn column(&self, linestr: &str) -> Option<usize> {
+    /* 1-based column counted in chars, which is what editors and CI annotations expect */
+    let span = self.span.as_ref()?;
+    Some(linestr.get(..span.start)?.chars().count() + 1)
+  }
+
+  pub fn end_column(&self, linestr: &str) -> Option<usize> {
+    /* Column just past the span, so an empty span has end_column == column */
+    let span = self.span.as_ref()?;
+    Some(self.column(linestr)? + linestr.get(span.clone())?.chars().count())
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventParseError {
+  NotEvent { loc: Location },
+  RegexMismatch { loc: Location },
+  BadDate { loc: Location, text: String, source: Option<chrono::ParseError> },
+  BadTime { loc: Location, text: String, source: Option<chrono::ParseError> },
+  EmptyTitle { loc: Location },
+}
+
+impl EventParseError {
+  fn bad_date(text: impl fmt::Display, source: Option<chrono::ParseError>) -> EventParseError {
+    EventParseError::BadDate { loc: Location::default(), text: text.to_string(), source }
+  }
+
+  fn bad_time(text: impl fmt::Display, source: Option<chrono::ParseError>) -> EventParseError {
+    EventParseError::BadTime { loc: Location::default(), text: text.to_string(), source }
+  }
+
+  pub fn loc(&self) -> &Location {
+    match self {
+      EventParseError::NotEvent { loc }
+      | EventParseError::RegexMismatch { loc }
+      | EventParseError::BadDate { loc, .. }
+      | EventParseError::BadTime { loc, .. }
+      | EventParseError::EmptyTitle { loc } => loc,
+    }
+  }
+
+  fn loc_mut(&mut self) -> &mut Location {
+    match self {
+      EventParseError::NotEvent { loc }
+      | EventParseError::RegexMismatch { loc }
+      | EventParseError::BadDate { loc, .. }
+      | EventParseError::BadTime { loc, .. }
+      | EventParseError::EmptyTitle { loc } => loc,
+    }
+  }
+
+  pub fn line(&self) -> Option<usize> {
+    self.loc().line
+  }
+
+  fn chrono_source(&self) -> Option<chrono::ParseError> {
+    match self {
+      EventParseError::BadDate { source, .. } | EventParseError::BadTime { source, .. } => *source,
+      _ => None,
+    }
+  }
+
+  pub fn span(&self) -> Option<Range<usize>> {
+    self.loc().span.clone()
+  }
+
+  pub fn with_line(mut self, line: usize) -> EventParseError {
+    self.loc_mut().line = Some(line);
+    self
+  }
+
+  pub fn rule(&self) -> &'static str {
+    /* Stable ids for CI output, don't rename these without a good reason */
+    match self {
+      EventParseError::NotEvent { .. } => "not-event",
+      EventParseError::RegexMismatch { .. } => "regex-mismatch",
+      EventParseError::BadDate { .. } => "bad-date",
+      EventParseError::BadTime { .. } => "bad-time",
+      EventParseError::EmptyTitle { .. } => "empty-title",
+    }
+  }
+
+  pub fn help(&self) -> &'static str {
+    match self {
+      EventParseError::NotEvent { .. } | EventParseError::RegexMismatch { .. } => "event lines look like `- [ ] (date) (time) (place) title`, leave a group empty with `()`",
+      EventParseError::BadDate { .. } => "expected `15 Feb`, `Feb 15`, `24-25 Feb 2024`, `28 Feb - 2 Mar`, `2024-02-15` or `2/15`",
+      EventParseError::BadTime { .. } => "expected `6 PM`, `6:30 PM`, `6-10PM` or `18:00-22:30`",
+      EventParseError::EmptyTitle { .. } => "put the event's name after the `(place)` group",
+    }
+  }
+
+  pub fn with_span(mut self, span: Range<usize>) -> EventParseError {
+    /* Only fills in a span that isn't there yet, the innermost parser knows best */
+    self.loc_mut().span.get_or_insert(span);
+    self
+  }
 }
 
 impl fmt::Display for EventParseError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-      write!(f, "Oh no can't parse that.")
+    match self {
+      EventParseError::NotEvent { .. } => write!(f, "not an event line, expected `- [ ] (date) (time) (place) title`"),
+      EventParseError::RegexMismatch { .. } => write!(f, "event line is missing one of the `(date) (time) (place)` groups"),
+      EventParseError::BadDate { text, source: Some(e), .. } => write!(f, "invalid date `{text}`: {e}"),
+      EventParseError::BadDate { text, source: None, .. } => write!(f, "invalid date `{text}`"),
+      EventParseError::BadTime { text, source: Some(e), .. } => write!(f, "invalid time `{text}`: {e}"),
+      EventParseError::BadTime { text, source: None, .. } => write!(f, "invalid time `{text}`"),
+      EventParseError::EmptyTitle { .. } => write!(f, "event has no title"),
+    }
+  }
+}
+
+impl Error for EventParseError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      EventParseError::BadDate { source: Some(e), .. } | EventParseError::BadTime { source: Some(e), .. } => Some(e),
+      _ => None,
+    }
   }
 }
 
@@ -26,8 +146,8 @@ mod my_date_ser {
 
 use super::EventModel;
 
-  const DATEFORMAT: &'static str = EventModel::DATEFMT;
-  const TIMEFORMAT: &'static str = EventModel::TIMEFMT;
+  const DATEFORMAT: &str = EventModel::DATEFMT;
+  const TIMEFORMAT: &str = EventModel::TIMEFMT;
 
   // The signature of a serialize_with function must follow the pattern:
   //
@@ -75,10 +195,45 @@ where
   serializer.serialize_str(&s)
 }
 
+pub fn serialize_tz_opt<S>(
+  tz: &Option<chrono_tz::Tz>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  S: Serializer,
+{
+  match tz {
+    Some(tz) => serializer.serialize_str(tz.name()),
+    _ => unreachable!(),
+  }
+}
+
 }
 
 #[derive(Default, Debug, Serialize)]
 pub struct EventModel {
+  uid: String, // Stable across runs, see derive_uid
+
+  #[serde(skip)]
+  implicit_rollover: bool, // end_date was pushed a day because the end time was before the start, without a `+1`
+
+  #[serde(skip)]
+  explicit_id: Option<ExplicitId>, // `^block-id` or `(id:...)` written on the line, wins over the derived uid
+
+  #[serde(skip)]
+  locale: Locale, // What the note was written in, for anything we show people
+
+  status: EventStatus,
+
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  crossed_out: bool, // `~~title~~` or the whole line struck through, past or called off depending on who wrote it
+
+  #[serde(skip_serializing_if = "is_zero")]
+  quote_depth: usize, // How many `>` the line is inside, callouts included
+
+  #[serde(skip_serializing_if = "is_zero")]
+  depth: usize, // List nesting, 0 for a top level item. Only the whole note can tell, see parsing::events_in_note
+
   #[serde(serialize_with = "my_date_ser::serialize_naive_date")]
   start_date: NaiveDate, // Make this just a datetime, mandatory
 
@@ -90,26 +245,173 @@ pub struct EventModel {
   
   #[serde(serialize_with = "my_date_ser::serialize_naive_time_opt", skip_serializing_if = "Option::is_none")]
   end_time: Option<NaiveTime>, // If None, all day
+
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  time_tba: bool, // `(TBA)`, there will be a time but nobody knows it yet, unlike an all day event
+
+  #[serde(serialize_with = "my_date_ser::serialize_naive_time_opt", skip_serializing_if = "Option::is_none")]
+  doors: Option<NaiveTime>, // From `(Doors 6PM, Show 7PM)`, start_time is the doors time when there is one
+
+  #[serde(serialize_with = "my_date_ser::serialize_naive_time_opt", skip_serializing_if = "Option::is_none")]
+  show: Option<NaiveTime>,
+
+  #[serde(serialize_with = "my_date_ser::serialize_tz_opt", skip_serializing_if = "Option::is_none")]
+  timezone: Option<Tz>, // If None, floating: whatever zone the reader is in
   
   place: String, // Should this be mandatory? yuh just empty string if None
   title: String, // This is mandatory, but just a String
 }
 
+// The Obsidian checkbox in front of the event, `- [x]` is one that's been and gone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+  #[default]
+  Open, // `[ ]`
+  Done, // `[x]`, attended or otherwise over with
+  Cancelled, // `[-]`
+  Deferred, // `[>]`, postponed to a date nobody knows yet
+  Tentative, // `[?]`
+  Important, // `[!]`
+}
+
+impl EventStatus {
+  pub fn from_checkbox(c: char) -> Option<EventStatus> {
+    match c {
+      ' ' => Some(EventStatus::Open),
+      'x' | 'X' => Some(EventStatus::Done),
+      '-' => Some(EventStatus::Cancelled),
+      '>' => Some(EventStatus::Deferred),
+      '?' => Some(EventStatus::Tentative),
+      '!' => Some(EventStatus::Important),
+      _ => None,
+    }
+  }
+
+  pub fn ical_status(self) -> &'static str {
+    /* RFC 5545 only has three for events, a postponed show is as good as unconfirmed */
+    match self {
+      EventStatus::Cancelled => "CANCELLED",
+      EventStatus::Deferred | EventStatus::Tentative => "TENTATIVE",
+      EventStatus::Open | EventStatus::Done | EventStatus::Important => "CONFIRMED",
+    }
+  }
+}
+
+// `(Doors 6PM, Show 7-10PM)`, each part is a label and anything parse_time_tup takes
+#[derive(Debug, Default, PartialEq)]
+struct LabeledTimes {
+  doors: Option<NaiveTime>,
+  show: Option<NaiveTime>,
+  end: Option<NaiveTime>, // Only the show can have an end
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ExplicitId {
+  Block(String), // Obsidian `^block-id`, only unique inside its note
+  Global(String), // `(id:...)`, the user promises it's unique across the vault
+}
+
+// Anything longer is a typo rather than an event, and it keeps Duration::minutes well inside what it can hold
+const MAX_DURATION_DAYS: f64 = 366.0;
+
+// A backwards range only wraps around New Year if it's at most this long once it does, `(5 Mar - 3 Feb)` is a typo
+const MAX_NEW_YEAR_WRAP_DAYS: i64 = 62;
+
+fn out_of_range() -> chrono::ParseError {
+  /* chrono has no public way to build its errors, a minute that can't exist gets us its OutOfRange one */
+  chrono::format::Parsed::new().set_minute(-1).unwrap_err()
+}
+
+fn is_zero(n: &usize) -> bool {
+  *n == 0
+}
+
+// 64 bit FNV-1a, std's DefaultHasher isn't guaranteed to be stable between Rust releases and uids have to be
+fn fnv1a(parts: &[&str]) -> u64 {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for part in parts {
+    for byte in part.bytes().chain(std::iter::once(0)) {
+      hash ^= byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    }
+  }
+  hash
+}
+
+fn localize(tz: Tz, naive: NaiveDateTime) -> Option<DateTime<Tz>> {
+  /*
+   * DST makes local times either ambiguous (fall back, the hour happens twice) or nonexistent (spring forward).
+   * Ambiguous takes the first one, since that's when the doors actually opened. Nonexistent moves past the gap
+   * like a wall clock would, so 2:30 AM on spring-forward day becomes 3:30 AM.
+   */
+  match tz.from_local_datetime(&naive) {
+    LocalResult::Single(dt) => Some(dt),
+    LocalResult::Ambiguous(earliest, _) => Some(earliest),
+    LocalResult::None => tz.from_local_datetime(&(naive + Duration::hours(1))).earliest(),
+  }
+}
+
 impl EventModel {
+  const UID_DOMAIN: &'static str = "md-event-parser";
+  // The checkbox and then `~~` right before the date group
+  const STRUCKLINEREG: &'static str = r"^(?:[ \t]*>)*[ \t]*(?:[-*+]|\d{1,9}[.)])[ \t]+\[.\] +~~\(";
+  const BLOCKIDREG: &'static str = r"\s*\^([A-Za-z0-9-]+)\s*$";
+  const IDREG: &'static str = r"\s*\(id: *([^)\s]+) *\)\s*$";
+  // A trailing word or IANA name after the time, ex `6-10PM CST` or `6 PM America/Chicago`
+  // `+1` after the time range, the end is that many days after the (end) date, ex `6PM-7PM +1`
+  const DAYOFFSETREG: &'static str = r"^(.*?)\s*\+(\d{1,2})$";
+  // How long it runs instead of when it ends, ex `7PM +3h`, `7PM for 90m` or `9AM, 2 days`
+  const DURATIONREG: &'static str = r"(?i)^(.*?)\s*(?:,|\bfor\b|\+)\s*(\d+(?:\.\d+)?)\s*(minutes?|mins?|m|hours?|hrs?|h|days?|d)$";
+  const TZSUFFIXREG: &'static str = r"^(.*\S)\s+([A-Za-z][A-Za-z_]*(?:/[A-Za-z0-9_+\-]+)*)$";
+
   const DATEFMT: &'static str = "%d %b %Y";
   const TIMEFMT: &'static str = "%I:%M %P";
   const TIMEFMT2: &'static str = "%I%M %P";
 
-  const TIMEREG1: &'static str = r"^\d{1,2}:\d\d [A,P]M$";
-  const TIMEREG2: &'static str = r"^(\d{1,2}) ([A,P]M)$";
-  const TIMEREG3: &'static str = r"^(\d{1,2})((?::\d\d)|)((?:[A,P]M|))-(\d{1,2})((?::\d\d)|) ?([A,P]M)$";
-
+  // Time and date groups go through these first, so the regexes below only ever see `-` and an uppercase `AM`/`PM`
+  // `6–10 PM`, `6 to 10 PM`, `6pm until 10pm`, en and em dashes, the minus sign and words all become `-`
+  const RANGESEPREG: &'static str = r"(?i)\s*(?:[-\u{2013}\u{2014}\u{2212}]|\bto\b|\buntil\b|\btill\b|\bthrough\b|\bthru\b)\s*";
+  // `7p`, `7pm`, `7 p.m.`, `7 PM`, the trailing group keeps `7 PT` or `7 AKST` from being read as a marker
+  const MERIDIEMREG: &'static str = r"(?i)(\d)\s*([ap])\.?(?:\s?m\.?)?($|[\s\-+])";
+
+  const NOONREG: &'static str = r"(?i)\bnoon\b";
+  const MIDNIGHTREG: &'static str = r"(?i)\bmidnight\b";
+  const TBAREG: &'static str = r"(?i)^(?:time )?(?:tba|tbd|tbc)$";
+  // One part of a labeled time group, ex `Doors 6PM` or `show: 7-10PM`
+  const LABELREG: &'static str = r"(?i)^(doors?|show)\s*:?\s*(.+)$";
+
+  const TIMEREG1: &'static str = r"^\d{1,2}:\d\d [AP]M$";
+  const TIMEREG2: &'static str = r"^(\d{1,2}) ([AP]M)$";
+  const TIMEREG3: &'static str = r"^(\d{1,2})((?::\d\d)|) ?((?:[AP]M|))-(\d{1,2})((?::\d\d)|) ?([AP]M)$";
+  // 2 cap groups, ex 18:00, 1930 or 18:00-22:30: 24-hour clock, the end is optional and can be 24:00
+  const TIMEREG4: &'static str = r"^(\d{1,2}:\d\d|\d{4})(?: ?- ?(\d{1,2}:\d\d|\d{4}))?$";
+  const TIMEFMT24: &'static str = "%H:%M";
+
+  // Month names are `[A-Za-zÀ-ÿ]`, which covers every locale's accents and builds much faster than `\p{L}`
   // 3 cap groups, ex 1 Feb or 20 Feb or 13Feb: Matches any date of form `%d %b` or `%d%b`, accepts year as empty string
-  const DATEREG1: &'static str = r"^(\d{1,2}) ?([a-zA-Z]{3,9}) ?(\d\d\d\d|\d\d|)$";
+  const DATEREG1: &'static str = r"^(\d{1,2}) ?([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d\d\d\d|\d\d|)$";
   // 4 cap groups, ex 01-4Feb,: Matches any date of form `%d-%d %b` or `%d-%d%b`, accepts year as empty string
-  const DATEREG2: &'static str = r"^(\d{1,2}) ?- ?(\d{1,2}) ?([a-zA-Z]{3,9}) ?(\d\d\d\d|\d\d|)$";
+  const DATEREG2: &'static str = r"^(\d{1,2}) ?- ?(\d{1,2}) ?([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d\d\d\d|\d\d|)$";
   // 5 cap groups, ex 28 Feb - 2 April: Matches any date of form `%d%b - %d %b`, accepts year as empty string
-  const DATEREG3: &'static str = r"^(\d{1,2}) ?([a-zA-Z]{3,9}) ?- ?(\d{1,2}) ?([a-zA-Z]{3,9}) ?(\d\d\d\d|\d\d|)$";
+  const DATEREG3: &'static str = r"^(\d{1,2}) ?([A-Za-zÀ-ÿ]{3,10}\.?) ?- ?(\d{1,2}) ?([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d\d\d\d|\d\d|)$";
+  // 3 or 6 cap groups, ex 2024-02-15 or 2024-02-15 to 2024-02-17: ISO 8601 calendar dates, the year is always written
+  const DATEREG4: &'static str = r"^(\d{4})-(\d{1,2})-(\d{1,2})(?:(?: ?- ?| to )(\d{4})-(\d{1,2})-(\d{1,2}))?$";
+  // 7 cap groups, ex 2/15, 15.02.2024 or 2/15 - 2/17/24: Numeric dates, `/` follows the config's order, `.` is always day first
+  const DATEREG5: &'static str = r"^(\d{1,2})([./])(\d{1,2})(?:[./](\d\d\d\d|\d\d))?(?:(?: ?- ?| to )(\d{1,2})[./](\d{1,2})(?:[./](\d\d\d\d|\d\d))?)?$";
+  // Month first, ex Nov 21, Nov 21-22 2024 or Feb 24 - Mar 2. Commas and ordinals are gone by the time these run
+  // 3 cap groups, ex Nov 21 or Nov 21 2024, the year has to be set off by a space so `Nov 2024` doesn't read as 20 Nov
+  const DATEREG6: &'static str = r"^([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d{1,2})(?: (\d\d\d\d|\d\d))?$";
+  // 4 cap groups, ex Nov 21-22 2024
+  const DATEREG7: &'static str = r"^([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d{1,2}) ?- ?(\d{1,2})(?: (\d\d\d\d|\d\d))?$";
+  // 6 cap groups, ex Feb 24 - Mar 2 or Dec 28 2024 - Jan 2 2025
+  const DATEREG8: &'static str = r"^([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d{1,2})(?: (\d\d\d\d|\d\d))? ?- ?([A-Za-zÀ-ÿ]{3,10}\.?) ?(\d{1,2})(?: (\d\d\d\d|\d\d))?$";
+  // `21st`, `2nd`, and the comma in `Nov 21, 2024` or `21 Nov, 2024`
+  // Also `1er` (fr), `1º` (es, pt) and the dot in `3. März` (de), but not the ones in `15.02.2024`
+  const ORDINALREG: &'static str = r"(?i)(\d)(?:st|nd|rd|th|er\b|\.?º|\.(\s*[A-Za-zÀ-ÿ]))";
+  // A leading weekday, ex `Sat 15 Feb` or `Samstag, 15. Feb`, only dropped if the date still reads without it
+  const WEEKDAYREG: &'static str = r"^([A-Za-zÀ-ÿ-]+\.?) (.+)$";
+  const DATECOMMAREG: &'static str = r"\s*,\s*";
 
   pub fn new(
     start_date: String,
@@ -121,7 +423,7 @@ impl EventModel {
   ) -> Result<EventModel> {
     let date_struct = match NaiveDate::parse_from_str(&start_date, EventModel::DATEFMT) {
       Ok(d) => d,
-      Err(_) => return Err(EventParseError::default()),
+      Err(e) => return Err(EventParseError::bad_date(&start_date, Some(e))),
     };
 
     /* time is hard bc I don't want to always do hour:minute I wanna leave it as like, 6 PM instead of 6:00 PM */
@@ -131,68 +433,360 @@ impl EventModel {
       None => "".to_owned(),
     };
       
-    Ok(EventModel { start_date: date_struct, title: title, place: p, ..Default::default()})
+    Ok(EventModel { start_date: date_struct, title, place: p, ..Default::default()})
   }
 
   pub fn from_line(
     linestr: String
   ) -> Result<EventModel> {
+    EventModel::from_line_with(linestr, &ParseConfig::default())
+  }
+
+  pub fn from_line_with(
+    linestr: String,
+    config: &ParseConfig
+  ) -> Result<EventModel> {
 
     if !parsing::line_is_event(&linestr) {
-      return Err(EventParseError { desc: format!("This line is not an event: {linestr}").to_owned() })
+      return Err(EventParseError::NotEvent { loc: Location::default() });
     }
 
-    let (datestr, timestr, placestr, titlestr) = EventModel::extract_from_line(&linestr)?;
+    let [date_span, time_span, place_span, title_span] = EventModel::locate_groups(&linestr)?;
+    let datestr = &linestr[date_span.clone()];
+    let timestr = &linestr[time_span.clone()];
+    let placestr = &linestr[place_span];
+    let titlestr = &linestr[title_span.clone()];
 
-    let mut ret = EventModel::default();
+    let mut ret = EventModel { status: EventModel::parse_status(&linestr)?, ..Default::default() };
+    ret.quote_depth = parsing::list_prefix(&linestr).map_or(0, |(quotes, _)| quotes);
 
-    let (start_date_struct, end_date_struct) = EventModel::parse_date_tup(datestr)?;
+    let (start_date_struct, end_date_struct) = EventModel::parse_date_tup_with(datestr, config).map_err(|e| e.with_span(date_span.clone()))?;
 
     if let Some(start_date_struct) = start_date_struct {
       ret.start_date = start_date_struct;
     } else {
-      return Err(EventParseError { desc: "Couldn't parse start_date, which is a necessary field".to_string() });
+      return Err(EventParseError::bad_date(datestr, None).with_span(date_span));
     }
     ret.end_date = match end_date_struct {
       Some(end) => end,
       None => start_date_struct.expect("To get here, we've already verified it's an Ok()"),
     };
 
-    let (start_time_struct, end_time_struct) = EventModel::parse_time_tup(timestr)?;
+    // The day marker and a duration can go on either side of the zone, `10PM-2AM +1 CST` or `7PM CST +3h`.
+    // A zone at the very end comes off first so the `+5` in `Etc/GMT+5` isn't taken for a day marker
+    let (timestr, tz) = EventModel::split_tz(timestr);
+    let (timestr, day_offset) = EventModel::split_day_offset(timestr);
+    let bad_duration = |e| EventParseError::bad_time(&linestr[time_span.clone()], Some(e)).with_span(time_span.clone());
+    let (timestr, duration) = EventModel::split_duration(timestr).map_err(bad_duration)?;
+    let (timestr, tz) = match tz {
+      Some(tz) => (timestr, Some(tz)),
+      None => EventModel::split_tz(timestr),
+    };
+    let (timestr, day_offset) = match day_offset {
+      Some(days) => (timestr, Some(days)),
+      None => EventModel::split_day_offset(timestr),
+    };
+    let (timestr, duration) = match duration {
+      Some(duration) => (timestr, Some(duration)),
+      None => EventModel::split_duration(timestr).map_err(bad_duration)?,
+    };
+    static TBA_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::TBAREG).unwrap());
+    ret.time_tba = TBA_REG.is_match(timestr);
+    let (start_time_struct, end_time_struct) = match EventModel::parse_labeled_times(timestr).map_err(|e| e.with_span(time_span.clone()))? {
+      _ if ret.time_tba => (None, None),
+      Some(labeled) => {
+        ret.doors = labeled.doors;
+        ret.show = labeled.show;
+        (labeled.doors.or(labeled.show), labeled.end)
+      },
+      None => EventModel::parse_time_tup(timestr).map_err(|e| e.with_span(time_span.clone()))?,
+    };
+    // Ending at 24:00 is ending at 00:00 a day later, on top of any written `+N`
+    let day_offset = match EventModel::ends_at_end_of_day(timestr) {
+      true => Some(day_offset.unwrap_or(0) + 1),
+      false => day_offset,
+    };
     ret.start_time = start_time_struct;
     ret.end_time   = end_time_struct;
+    ret.timezone   = tz.or(config.default_tz);
+    ret.locale     = config.locale;
+
+    if let Some(duration) = duration {
+      // The length of a show is counted from the show, not from when the doors open
+      let end = match (ret.show.or(ret.start_time), ret.end_time, day_offset) {
+        (Some(from), None, None) if ret.start_date == ret.end_date => ret.start_date.and_time(from).checked_add_signed(duration),
+        _ => None,
+      }.ok_or(EventParseError::bad_time(&linestr[time_span.clone()], None).with_span(time_span.clone()))?;
+      ret.end_date = end.date();
+      ret.end_time = Some(end.time());
+    }
+
+    match (day_offset, ret.start_time, ret.end_time) {
+      (Some(_), _, None) => return Err(EventParseError::bad_time(&linestr[time_span.clone()], None).with_span(time_span)),
+      (Some(days), _, Some(_)) => {
+        ret.end_date = ret.end_date.checked_add_days(Days::new(days))
+          .ok_or(EventParseError::bad_time(&linestr[time_span.clone()], None).with_span(time_span))?;
+      },
+      (None, Some(start), Some(end)) if ret.start_date == ret.end_date && end < start => {
+        // Shows like `(10PM-2AM)` run past midnight, the end time belongs to the next day
+        ret.end_date = ret.end_date.succ_opt().unwrap_or(ret.end_date);
+        ret.implicit_rollover = true;
+      },
+      _ => {},
+    }
 
     ret.place = placestr.to_string();
+    let (titlestr, explicit_id) = EventModel::split_explicit_id(titlestr);
+    let (titlestr, crossed_out) = EventModel::split_strikethrough(&linestr, titlestr);
+    ret.crossed_out = crossed_out;
     if !titlestr.is_empty() {
-      ret.title = titlestr.to_string();
+      ret.title = titlestr;
     } else {
-      return Err(EventParseError { desc: "No empty titles allowed loser".to_string() });
+      return Err(EventParseError::EmptyTitle { loc: Location { line: None, span: Some(title_span) } });
     }
+    ret.explicit_id = explicit_id;
+    ret.set_source("");
 
     Ok(ret)
   }
 
+  fn split_day_offset(timestr: &str) -> (&str, Option<u64>) {
+    static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::DAYOFFSETREG).unwrap());
+    match REG.captures(timestr) {
+      Some(caps) => (caps.get(1).unwrap().as_str(), caps[2].parse().ok()),
+      None => (timestr, None),
+    }
+  }
+
+  fn split_duration(timestr: &str) -> ParseResult<(&str, Option<Duration>)> {
+    /* Errs on a duration too long to be an event, rather than letting Duration::minutes panic on it */
+    static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::DURATIONREG).unwrap());
+    let Some(caps) = REG.captures(timestr) else {
+      return Ok((timestr, None));
+    };
+    let minutes_per = match caps[3].to_lowercase().chars().next() {
+      Some('d') => 24 * 60,
+      Some('h') => 60,
+      _ => 1,
+    };
+    let Ok(amount) = caps[2].parse::<f64>() else {
+      return Ok((timestr, None));
+    };
+    let minutes = (amount * minutes_per as f64).round();
+    if !minutes.is_finite() || minutes > MAX_DURATION_DAYS * 24.0 * 60.0 {
+      return Err(out_of_range());
+    }
+    Ok((caps.get(1).unwrap().as_str(), Some(Duration::minutes(minutes as i64))))
+  }
+
+  fn split_tz(timestr: &str) -> (&str, Option<Tz>) {
+    /* Only strips the suffix if it really is a zone, `6 PM` ends in a word too */
+    static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::TZSUFFIXREG).unwrap());
+    if let Some(caps) = REG.captures(timestr) {
+      if let Some(tz) = config::parse_tz(&caps[2]) {
+        return (caps.get(1).unwrap().as_str(), Some(tz));
+      }
+    }
+    (timestr, None)
+  }
+
+  fn split_explicit_id(titlestr: &str) -> (String, Option<ExplicitId>) {
+    /* Pulls `^block-id` and then `(id:...)` off the end of the title, (id:...) wins if both are there */
+    static BLOCK_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::BLOCKIDREG).unwrap());
+    static ID_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::IDREG).unwrap());
+    let (block_reg, id_reg) = (&*BLOCK_REG, &*ID_REG);
+
+    let mut explicit_id = block_reg.captures(titlestr).map(|c| ExplicitId::Block(c[1].to_string()));
+    let title = block_reg.replace(titlestr, "");
+    if let Some(c) = id_reg.captures(&title) {
+      explicit_id = Some(ExplicitId::Global(c[1].to_string()));
+    }
+    let title = id_reg.replace(&title, "");
+
+    (title.trim().to_string(), explicit_id)
+  }
+
+  fn split_strikethrough(linestr: &str, titlestr: String) -> (String, bool) {
+    /*
+     * `~~title~~`, or `- [ ] ~~(date) (time) (place) title~~` where the opening tildes are before the date.
+     * Only a strike over the whole title counts, `Polyphia ~~w/ Covet~~` keeps its tildes and isn't crossed out.
+     */
+    static LINE_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::STRUCKLINEREG).unwrap());
+    let line_struck = LINE_REG.is_match(linestr);
+    let inner = match titlestr.strip_suffix("~~") {
+      Some(rest) if line_struck => Some(rest),
+      Some(rest) => rest.strip_prefix("~~"),
+      None => None,
+    };
+    match inner {
+      Some(inner) if !inner.contains("~~") => (inner.trim().to_string(), true),
+      _ => (titlestr, false),
+    }
+  }
+
+  pub fn derive_uid(&self, source: &str) -> String {
+    /*
+     * Only the note and the parts of the event that identify it go into the hash, so fixing the time or a
+     * venue typo keeps the uid. Titles are compared without case or punctuation for the same reason.
+     */
+    let hash = match &self.explicit_id {
+      Some(ExplicitId::Global(id)) => return format!("{id}@{}", EventModel::UID_DOMAIN),
+      Some(ExplicitId::Block(id)) => fnv1a(&[source, "^", id]),
+      None => {
+        let title: String = self.title.chars()
+          .filter(|c| c.is_alphanumeric())
+          .flat_map(char::to_lowercase)
+          .collect();
+        fnv1a(&[source, &self.start_date.format("%Y-%m-%d").to_string(), &title])
+      },
+    };
+    format!("{hash:016x}@{}", EventModel::UID_DOMAIN)
+  }
+
+  pub fn set_source(&mut self, source: &str) {
+    /* source is the note's path relative to the vault root, with `/` separators so uids match across machines */
+    self.uid = self.derive_uid(source);
+  }
+
+  pub fn uid(&self) -> &str {
+    &self.uid
+  }
+
+  pub fn start_date(&self) -> NaiveDate {
+    self.start_date
+  }
+
+  pub fn end_date(&self) -> NaiveDate {
+    self.end_date
+  }
+
+  pub fn start_time(&self) -> Option<NaiveTime> {
+    self.start_time
+  }
+
+  pub fn end_time(&self) -> Option<NaiveTime> {
+    self.end_time
+  }
+
+  pub fn timezone(&self) -> Option<Tz> {
+    self.timezone
+  }
+
+  pub fn status(&self) -> EventStatus {
+    self.status
+  }
+
+  pub fn crossed_out(&self) -> bool {
+    self.crossed_out
+  }
+
+  pub fn quote_depth(&self) -> usize {
+    self.quote_depth
+  }
+
+  pub fn depth(&self) -> usize {
+    self.depth
+  }
+
+  pub fn set_depth(&mut self, depth: usize) {
+    self.depth = depth;
+  }
+
+  pub fn locale(&self) -> Locale {
+    self.locale
+  }
+
+  pub fn time_tba(&self) -> bool {
+    self.time_tba
+  }
+
+  pub fn all_day(&self) -> bool {
+    self.start_time.is_none() && !self.time_tba
+  }
+
+  pub fn doors(&self) -> Option<NaiveTime> {
+    self.doors
+  }
+
+  pub fn show(&self) -> Option<NaiveTime> {
+    self.show
+  }
+
+  pub fn implicit_rollover(&self) -> bool {
+    self.implicit_rollover
+  }
+
+  pub fn duration(&self) -> Option<Duration> {
+    /*
+     * All day events last whole days, end date included. Zoned times go through their zone so a night that
+     * crosses a DST change comes out an hour shorter or longer. Events with only a start, or a time TBA, have none.
+     */
+    if self.all_day() {
+      return Some(self.end_date.signed_duration_since(self.start_date) + Duration::days(1));
+    }
+    match (self.start_datetime(), self.end_datetime()) {
+      (Some(start), Some(end)) => Some(end.signed_duration_since(start)),
+      _ => Some(self.end_naive()?.signed_duration_since(self.start_naive()?)),
+    }
+  }
+
+  pub fn start_naive(&self) -> Option<NaiveDateTime> {
+    self.start_time.map(|t| self.start_date.and_time(t))
+  }
+
+  pub fn end_naive(&self) -> Option<NaiveDateTime> {
+    self.end_time.map(|t| self.end_date.and_time(t))
+  }
+
+  pub fn start_datetime(&self) -> Option<DateTime<Tz>> {
+    localize(self.timezone?, self.start_naive()?)
+  }
+
+  pub fn end_datetime(&self) -> Option<DateTime<Tz>> {
+    localize(self.timezone?, self.end_naive()?)
+  }
+
+  pub fn place(&self) -> &str {
+    &self.place
+  }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+
+  pub fn locate_groups(haystack: &str) -> Result<[Range<usize>; 4]> {
+    /* Byte ranges of the date, time, place and title groups, so errors can point at the one that failed */
+    let temp = parsing::event_captures(haystack).ok_or(EventParseError::RegexMismatch { loc: Location::default() })?;
+    let group = |i: usize| temp.get(i).expect("EVENTREGEX has 5 groups that always participate").range();
+    // The title goes on past a trailing `(id:...)` that event_captures left out
+    Ok([group(2), group(3), group(4), group(5).start..haystack.len()])
+  }
+
+  fn parse_status(haystack: &str) -> Result<EventStatus> {
+    let temp = parsing::event_captures(haystack).ok_or(EventParseError::RegexMismatch { loc: Location::default() })?;
+    let checkbox = temp[1].chars().next().expect("EVENTREGEX's checkbox is one char");
+    Ok(EventStatus::from_checkbox(checkbox).expect("EVENTREGEX only lets known checkboxes through"))
+  }
+
   pub fn extract_from_line<'a>(haystack: &'a str) -> Result<(&'a str, &'a str, &'a str, &'a str)> {
 
     // A lot of the string processing is instead done by only capturing the regex we want, 
     // e.g. we don't have to trim the - [ ] prefix
 
-    let reg = Regex::new(parsing::EVENTREGEX).unwrap();
+    let [date_span, time_span, place_span, title_span] = EventModel::locate_groups(haystack)?;
 
-    let temp = reg.captures(haystack).ok_or(EventParseError{desc: "Line regex didn't match".to_owned()})?;
+    let datestr: &'a str = &haystack[date_span];
+    let timestr: &'a str = &haystack[time_span];
+    let placestr: &'a str = &haystack[place_span];
+    let titlestr: &'a str = &haystack[title_span];
 
-    let datestr: &'a str = temp.extract::<4>().1.get(0).unwrap();
-    let timestr: &'a str = temp.extract::<4>().1.get(1).unwrap();
-    let placestr: &'a str = temp.extract::<4>().1.get(2).unwrap();
-    let titlestr: &'a str = temp.extract::<4>().1.get(3).unwrap();
-
-    return Ok((datestr, timestr, placestr, titlestr));
+    Ok((datestr, timestr, placestr, titlestr))
   }
 
   pub fn with_date(date_str: String) -> Result<EventModel> {
     let date_struct = match NaiveDate::parse_from_str(&date_str, EventModel::DATEFMT) {
       Ok(d) => d,
-      Err(_) => return Err(EventParseError::default()),
+      Err(e) => return Err(EventParseError::bad_date(&date_str, Some(e))),
     };
     Ok(EventModel{
       start_date:date_struct,
@@ -210,6 +804,80 @@ impl EventModel {
     NaiveDate::parse_from_str(&datestr.into(), EventModel::DATEFMT)
   }
 
+  fn base_parse_time24(timestr: &str) -> ParseResult<NaiveTime> {
+    /* `1930` is `19:30` without the colon, TIMEREG4 only lets 4 digit runs through so the split is safe */
+    match timestr.contains(':') {
+      true => NaiveTime::parse_from_str(timestr, EventModel::TIMEFMT24),
+      false => NaiveTime::parse_from_str(&format!("{}:{}", &timestr[..2], &timestr[2..]), EventModel::TIMEFMT24),
+    }
+  }
+
+  fn is_end_of_day(timestr: &str) -> bool {
+    timestr == "24:00" || timestr == "2400"
+  }
+
+  fn normalize_range(rangestr: &str) -> String {
+    static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::RANGESEPREG).unwrap());
+    REG.replace_all(rangestr.trim(), "-").into_owned()
+  }
+
+  fn strip_weekday(datestr: &str, locale: Locale) -> Option<&str> {
+    /* What's left of `Sat 24 Feb` without the weekday, None if the first word isn't one */
+    static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::WEEKDAYREG).unwrap());
+    let caps = REG.captures(datestr)?;
+    locale.weekday(&caps[1]).map(|_| caps.get(2).unwrap().as_str())
+  }
+
+  fn normalize_date(datestr: &str) -> String {
+    /* `Feb 24th – Mar 2nd, 2024` becomes `Feb 24-Mar 2 2024` */
+    let datestr = EventModel::normalize_range(datestr);
+    static ORDINAL_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::ORDINALREG).unwrap());
+    static COMMA_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::DATECOMMAREG).unwrap());
+    let datestr = ORDINAL_REG.replace_all(&datestr, "$1$2");
+    COMMA_REG.replace_all(&datestr, " ").into_owned()
+  }
+
+  fn normalize_time(timestr: &str) -> String {
+    /* `6pm–10 p.m.` becomes `6 PM-10 PM`, always a space before the marker since that's what TIMEFMT wants */
+    static MERIDIEM_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::MERIDIEMREG).unwrap());
+    static NOON_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::NOONREG).unwrap());
+    static MIDNIGHT_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::MIDNIGHTREG).unwrap());
+    let timestr = EventModel::normalize_range(timestr);
+    let timestr = NOON_REG.replace_all(&timestr, "12 PM");
+    let timestr = MIDNIGHT_REG.replace_all(&timestr, "12 AM");
+    MERIDIEM_REG.replace_all(&timestr, |caps: &regex::Captures| {
+      format!("{} {}M{}", &caps[1], caps[2].to_uppercase(), &caps[3])
+    }).into_owned()
+  }
+
+  fn ends_at_end_of_day(timestr: &str) -> bool {
+    /* `(20:00-24:00)` ends at midnight of the next day, which NaiveTime can only say as 00:00 */
+    static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::TIMEREG4).unwrap());
+    REG.captures(&EventModel::normalize_time(timestr)).and_then(|c| c.get(2)).is_some_and(|end| EventModel::is_end_of_day(end.as_str()))
+  }
+
+  fn parse_labeled_times(timestr: &str) -> Result<Option<LabeledTimes>> {
+    /* None if the group isn't labeled at all, so the caller falls back to parse_time_tup */
+    static LABEL_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EventModel::LABELREG).unwrap());
+    let reg = &*LABEL_REG;
+    if !reg.is_match(timestr.trim()) {
+      return Ok(None);
+    }
+
+    let mut ret = LabeledTimes::default();
+    for part in timestr.split([',', ';']) {
+      let caps = reg.captures(part.trim()).ok_or(EventParseError::bad_time(timestr, None))?;
+      let (start, end) = EventModel::parse_time_tup(caps[2].trim()).map_err(|e| EventParseError::bad_time(timestr, e.chrono_source()))?;
+      if caps[1].to_lowercase().starts_with("door") {
+        ret.doors = start;
+      } else {
+        ret.show = start;
+        ret.end = end;
+      }
+    }
+    Ok(Some(ret))
+  }
+
   fn parse_time_tup(timestr: impl Into<String> + AsRef<str> + std::fmt::Display + PartialEq<String>) -> Result<(Option<NaiveTime>, Option<NaiveTime>)> {
     /** List of ways I might write time?
      * form a) 6 PM
@@ -222,19 +890,22 @@ impl EventModel {
       return Ok((None, None));
     }
 
-    let time_reg_arr: [Regex; 3] = [
+    let normalized = Self::normalize_time(timestr.as_ref());
+    static TIME_REG_ARR: LazyLock<[Regex; 4]> = LazyLock::new(|| [
         Regex::new(EventModel::TIMEREG1).unwrap(),
         Regex::new(EventModel::TIMEREG2).unwrap(),
         Regex::new(EventModel::TIMEREG3).unwrap(),
-      ];
+        Regex::new(EventModel::TIMEREG4).unwrap(),
+      ]);
+    let time_reg_arr = &*TIME_REG_ARR;
 
-    if time_reg_arr[0].is_match(timestr.as_ref()) {
+    if time_reg_arr[0].is_match(&normalized) {
       // println!("String {timestr} matches regex {:?}", time_reg_arr[0]);
       // Simple/well-formed case
-      let start_time_struct = Self::base_parse_time(timestr).map_err(|e| EventParseError{desc: e.to_string()})?;
+      let start_time_struct = Self::base_parse_time(normalized.as_str()).map_err(|e| EventParseError::bad_time(&timestr, Some(e)))?;
       return Ok((Some(start_time_struct), None));
 
-    } else if let Some(mat) = time_reg_arr[1].captures(timestr.as_ref()) {
+    } else if let Some(mat) = time_reg_arr[1].captures(&normalized) {
       // println!("String {timestr} matches regex {:?}", time_reg_arr[1]);
       // This is single time with no `:\d\d`
       let mut modstr: String = String::new();
@@ -243,10 +914,10 @@ impl EventModel {
       modstr.push_str(":00 ");
       // Pushes the AM or PM
       modstr.push_str(mat.extract::<2>().1[1]);
-      let start_time_struct = Self::base_parse_time(&modstr).map_err(|e| EventParseError{desc:e.to_string()})?;
+      let start_time_struct = Self::base_parse_time(&modstr).map_err(|e| EventParseError::bad_time(&timestr, Some(e)))?;
       return Ok((Some(start_time_struct), None));
 
-    } else if let Some(mat) = time_reg_arr[2].captures(timestr.as_ref()) {
+    } else if let Some(mat) = time_reg_arr[2].captures(&normalized) {
       // println!("String {timestr} matches regex {:?}", time_reg_arr[2]);
       /**
        * This is the most complicated case, we have up to 5 capture groups.
@@ -275,7 +946,7 @@ impl EventModel {
       startstr.push_str(cap1);
       if !cap2.is_empty() {
         startstr.push_str(cap2);
-        startstr.push_str(" ");
+        startstr.push(' ');
       } else {
         startstr.push_str(":00 ");
       }
@@ -289,19 +960,29 @@ impl EventModel {
       endstr.push_str(cap4);
       if !cap5.is_empty() {
         endstr.push_str(cap5);
-        endstr.push_str(" ");
+        endstr.push(' ');
       } else {
         endstr.push_str(":00 ");
       }
       endstr.push_str(cap6);
       
-      let start_time_struct = Self::base_parse_time(&startstr).map_err(|e| EventParseError{desc:e.to_string()}).ok();
-      let end_time_struct = Self::base_parse_time(&endstr).ok();
-
-      return Ok((start_time_struct, end_time_struct));
+      let start_time_struct = Self::base_parse_time(&startstr).map_err(|e| EventParseError::bad_time(&timestr, Some(e)))?;
+      let end_time_struct = Self::base_parse_time(&endstr).map_err(|e| EventParseError::bad_time(&timestr, Some(e)))?;
+
+      return Ok((Some(start_time_struct), Some(end_time_struct)));
+
+    } else if let Some(mat) = time_reg_arr[3].captures(&normalized) {
+      // 24-hour, 24:00 is only allowed as the end and comes back as midnight, see ends_at_end_of_day
+      let start_time_struct = Self::base_parse_time24(&mat[1]).map_err(|e| EventParseError::bad_time(&timestr, Some(e)))?;
+      let end_time_struct = match mat.get(2) {
+        Some(end) if Self::is_end_of_day(end.as_str()) => Some(NaiveTime::MIN),
+        Some(end) => Some(Self::base_parse_time24(end.as_str()).map_err(|e| EventParseError::bad_time(&timestr, Some(e)))?),
+        None => None,
+      };
+      return Ok((Some(start_time_struct), end_time_struct));
 
     } else {
-      return Err(EventParseError { desc: "Something fialed in parse_time_tup".to_owned() });
+      return Err(EventParseError::bad_time(&timestr, None));
     }
 
     unreachable!();
@@ -319,72 +1000,220 @@ impl EventModel {
   }
 
   fn parse_date_tup(datestr: impl Into<String> + AsRef<str> + std::fmt::Display + PartialEq<String>) -> Result<(Option<NaiveDate>, Option<NaiveDate>)> {
+    EventModel::parse_date_tup_with(datestr, &ParseConfig::default())
+  }
+
+  pub fn is_date_with(datestr: &str, config: &ParseConfig) -> bool {
+    /* Whether a date group would parse, for lint to tell a mistyped event from a to-do that starts with a paren */
+    EventModel::parse_date_tup_with(datestr, config).is_ok_and(|(start, _)| start.is_some())
+  }
+
+  fn explicit_year(year_str: &str, config: &ParseConfig) -> Option<i32> {
+    /* The year group is allowed to be empty, that's when the config has to guess it. `24` is 2024, not 24 AD */
+    year_str.parse().ok().map(|year| config.expand_year(year))
+  }
+
+  fn date_in_year(day_str: &str, month_str: &str, year: i32, locale: Locale) -> ParseResult<NaiveDate> {
+    /* Month names come from the locale's tables, anything they don't know still goes to chrono so there's an error to show */
+    match locale.month(month_str) {
+      Some(month) => Self::numeric_date_in_year(day_str, &month.to_string(), year),
+      None => Self::base_parse_date(format!("{day_str} {month_str} {year}")),
+    }
+  }
+
+  fn numeric_date_in_year(day_str: &str, month_str: &str, year: i32) -> ParseResult<NaiveDate> {
+    /* Goes through chrono's parser rather than from_ymd_opt so a 31 Feb still comes back with a reason */
+    NaiveDate::parse_from_str(&format!("{year}-{month_str}-{day_str}"), "%Y-%m-%d")
+  }
+
+  fn date_range(
+    start_in_year: impl Fn(i32) -> ParseResult<NaiveDate>,
+    end_in_year: impl Fn(i32) -> ParseResult<NaiveDate>,
+    start_year: Option<i32>,
+    end_year: Option<i32>,
+    config: &ParseConfig
+  ) -> ParseResult<(NaiveDate, NaiveDate)> {
+    /*
+     * A range with a single written year shares it between both ends. If the end comes out before the start
+     * and the range is short once it wraps around New Year, like `28 Dec - 2 Jan`, it does: a year written on
+     * the end belongs to the end, `28 Dec - 2 Jan 2025` starts in 2024, otherwise the end moves into the next
+     * year. Anything else stays backwards so lint can point at it.
+     */
+    let wraps = |start: NaiveDate, end: NaiveDate| {
+      end.month() < start.month() && (end - start).num_days() <= MAX_NEW_YEAR_WRAP_DAYS
+    };
+    match (start_year, end_year) {
+      (Some(start_year), Some(end_year)) => Ok((start_in_year(start_year)?, end_in_year(end_year)?)),
+      (None, Some(year)) => {
+        let end = end_in_year(year)?;
+        let start = start_in_year(year)?;
+        match start_in_year(year - 1) {
+          Ok(wrapped) if start > end && wraps(wrapped, end) => Ok((wrapped, end)),
+          _ => Ok((start, end)),
+        }
+      },
+      (start_year, None) => {
+        let start = match start_year {
+          Some(year) => start_in_year(year)?,
+          None => config.infer_year(&start_in_year)?,
+        };
+        let end = end_in_year(start.year())?;
+        match end_in_year(start.year() + 1) {
+          Ok(wrapped) if end < start && wraps(start, wrapped) => Ok((start, wrapped)),
+          _ => Ok((start, end)),
+        }
+      },
+    }
+  }
+
+  fn parse_date_tup_with(datestr: impl Into<String> + AsRef<str> + std::fmt::Display + PartialEq<String>, config: &ParseConfig) -> Result<(Option<NaiveDate>, Option<NaiveDate>)> {
 
     // Must have a start date, end date is optional (== start date if none)
-    /** List of ways I might write date:
+    /* List of ways I might write date:
      * 12 Feb
      * 12-14 Feb
      * 27 Feb - 3 April
+     * 28 Dec - 2 Jan (crosses into the next year)
+     * 2024-02-15 or 2024-02-15 to 2024-02-17
+     * 2/15, 15.02.2024 or 2/15 - 2/17
+     * Nov 21, Nov 21-22, 2024 or Feb 24th - Mar 2nd
      */
 
-    let date_reg_arr: [Regex; 3] = [
+    // Built once, every event line runs through these and compiling them per line is most of the parse time
+    static DATE_REG_ARR: LazyLock<[Regex; 8]> = LazyLock::new(|| [
         Regex::new(EventModel::DATEREG1).unwrap(),
         Regex::new(EventModel::DATEREG2).unwrap(),
         Regex::new(EventModel::DATEREG3).unwrap(),
-      ];
+        Regex::new(EventModel::DATEREG4).unwrap(),
+        Regex::new(EventModel::DATEREG5).unwrap(),
+        Regex::new(EventModel::DATEREG6).unwrap(),
+        Regex::new(EventModel::DATEREG7).unwrap(),
+        Regex::new(EventModel::DATEREG8).unwrap(),
+      ]);
+    let date_reg_arr = &*DATE_REG_ARR;
+
+    let normalized = Self::normalize_date(datestr.as_ref());
+    // `mar 15` is March in Spanish as much as it's Tuesday, so the weekday only comes off if the date still reads without it
+    let normalized = match Self::strip_weekday(&normalized, config.locale) {
+      Some(rest) if date_reg_arr.iter().any(|reg| reg.is_match(rest)) => rest,
+      _ => normalized.as_str(),
+    };
+    let date_in_year = |day_str: &str, month_str: &str, year| Self::date_in_year(day_str, month_str, year, config.locale);
+    let bad_date = |e| EventParseError::bad_date(&datestr, Some(e));
 
-    let current_year = Utc::now().year();
-    
-    if let Some(mat) = date_reg_arr[0].captures(datestr.as_ref()) {
+    if let Some(mat) = date_reg_arr[0].captures(normalized) {
       // println!("String {datestr} matches regex {:?}", date_reg_arr[0]);
       // Simple/well-formed case, just need to check for year
-      if !mat.extract::<3>().1[2].is_empty() { // Year not blank
-        let start_date_struct = Self::base_parse_date(datestr).map_err(|e| EventParseError{desc: e.to_string()})?;
-        return Ok((Some(start_date_struct), None));  
-      } else { // Year is blank
-        let start_date_struct = Self::base_parse_date(format!("{datestr} {current_year}")).map_err(|e| EventParseError{desc: e.to_string()})?;
-        return Ok((Some(start_date_struct), None));
-      }
-
-    } else if let Some(mat) = date_reg_arr[1].captures(datestr.as_ref()) {
+      let [day_str, month_str, year_str] = mat.extract::<3>().1;
+      let start_date_struct = match Self::explicit_year(year_str, config) {
+        Some(year) => date_in_year(day_str, month_str, year),
+        None => config.infer_year(|year| date_in_year(day_str, month_str, year)),
+      }.map_err(bad_date)?;
+      Ok((Some(start_date_struct), None))
+
+    } else if let Some(mat) = date_reg_arr[1].captures(normalized) {
       // println!("String {datestr} matches regex {:?}", date_reg_arr[1]);
       // This is a date range of form (\d\d) ?- ?(\d\d) ?(MONTH) ?(YEAR)
-      // Where year can be empty (put current year in this case)
-      let start_day_str = mat.extract::<4>().1[0];
-      let end_day_str = mat.extract::<4>().1[1];
-      let month_str = mat.extract::<4>().1[2];
-      let year_str = match mat.extract::<4>().1[3] {
-        "" => Utc::now().year().to_string(),
-        x => x.to_string(),
-      };
-
-      let start_date_str = format!("{start_day_str} {month_str} {year_str}");
-      let end_date_str = format!("{end_day_str} {month_str} {year_str}");
+      // Where year can be empty (the config guesses it from the start date in this case)
+      let [start_day_str, end_day_str, month_str, year_str] = mat.extract::<4>().1;
 
-      let start_date_struct = Self::base_parse_date(start_date_str).map_err(|e| EventParseError{desc: e.to_string()})?;
-      let end_date_struct = Self::base_parse_date(end_date_str).map_err(|e| EventParseError{desc: e.to_string()})?;
-      return Ok((Some(start_date_struct), Some(end_date_struct)));
+      let start_date_struct = match Self::explicit_year(year_str, config) {
+        Some(year) => date_in_year(start_day_str, month_str, year),
+        None => config.infer_year(|year| date_in_year(start_day_str, month_str, year)),
+      }.map_err(bad_date)?;
+      let end_date_struct = date_in_year(end_day_str, month_str, start_date_struct.year()).map_err(bad_date)?;
+      Ok((Some(start_date_struct), Some(end_date_struct)))
 
-    } else if let Some(mat) = date_reg_arr[2].captures(datestr.as_ref()) {
+    } else if let Some(mat) = date_reg_arr[2].captures(normalized) {
       // println!("String {datestr} matches regex {:?}", date_reg_arr[1]);
       // 5 cap groups, ex 28 Feb - 2 April: Matches any date of form `%d%b - %d %b`, accepts year as empty string
-      let start_day_str = mat.extract::<5>().1[0];
-      let start_month_str = mat.extract::<5>().1[1];
-      let end_day_str = mat.extract::<5>().1[2];
-      let end_month_str = mat.extract::<5>().1[3];
-      let year_str = match mat.extract::<5>().1[4] {
-        "" => Utc::now().year().to_string(),
-        x => x.to_string(),
+      let [start_day_str, start_month_str, end_day_str, end_month_str, year_str] = mat.extract::<5>().1;
+
+      let (start_date_struct, end_date_struct) = Self::date_range(
+        |year| date_in_year(start_day_str, start_month_str, year),
+        |year| date_in_year(end_day_str, end_month_str, year),
+        None,
+        Self::explicit_year(year_str, config),
+        config,
+      ).map_err(bad_date)?;
+      Ok((Some(start_date_struct), Some(end_date_struct)))
+
+    } else if let Some(mat) = date_reg_arr[3].captures(normalized) {
+      // ISO, the end is optional but when it's there it's a full date too
+      let start_date_struct = Self::numeric_date_in_year(&mat[3], &mat[2], mat[1].parse().unwrap_or_default()).map_err(bad_date)?;
+      let end_date_struct = match (mat.get(4), mat.get(5), mat.get(6)) {
+        (Some(year), Some(month), Some(day)) => {
+          Some(Self::numeric_date_in_year(day.as_str(), month.as_str(), year.as_str().parse().unwrap_or_default()).map_err(bad_date)?)
+        },
+        _ => None,
       };
+      Ok((Some(start_date_struct), end_date_struct))
 
-      let start_date_str = format!("{start_day_str} {start_month_str} {year_str}");
-      let end_date_str = format!("{end_day_str} {end_month_str} {year_str}");
+    } else if let Some(mat) = date_reg_arr[4].captures(normalized) {
+      // Numeric, 1 and 3 are the first two numbers of the start, 5 and 6 of the end, 4 and 7 the optional years
+      let day_first = &mat[2] == "." || config.date_order == DateOrder::DayFirst;
+      let day_month = |first: &'_ str, second: &'_ str| -> (String, String) {
+        if day_first { (first.to_owned(), second.to_owned()) } else { (second.to_owned(), first.to_owned()) }
+      };
+      let year = |i: usize| mat.get(i).and_then(|y| Self::explicit_year(y.as_str(), config));
+
+      let (start_day_str, start_month_str) = day_month(&mat[1], &mat[3]);
+      let start_in_year = |year| Self::numeric_date_in_year(&start_day_str, &start_month_str, year);
+
+      match (mat.get(5), mat.get(6)) {
+        (Some(first), Some(second)) => {
+          let (end_day_str, end_month_str) = day_month(first.as_str(), second.as_str());
+          let (start_date_struct, end_date_struct) = Self::date_range(
+            start_in_year,
+            |year| Self::numeric_date_in_year(&end_day_str, &end_month_str, year),
+            year(4),
+            year(7),
+            config,
+          ).map_err(bad_date)?;
+          Ok((Some(start_date_struct), Some(end_date_struct)))
+        },
+        _ => {
+          let start_date_struct = match year(4) {
+            Some(year) => start_in_year(year),
+            None => config.infer_year(start_in_year),
+          }.map_err(bad_date)?;
+          Ok((Some(start_date_struct), None))
+        },
+      }
+
+    } else if let Some(mat) = date_reg_arr[5].captures(normalized) {
+      // Month first single date, same as the first case the other way around
+      let (month_str, day_str) = (&mat[1], &mat[2]);
+      let start_date_struct = match mat.get(3).and_then(|y| Self::explicit_year(y.as_str(), config)) {
+        Some(year) => date_in_year(day_str, month_str, year),
+        None => config.infer_year(|year| date_in_year(day_str, month_str, year)),
+      }.map_err(bad_date)?;
+      Ok((Some(start_date_struct), None))
+
+    } else if let Some(mat) = date_reg_arr[6].captures(normalized) {
+      let (month_str, start_day_str, end_day_str) = (&mat[1], &mat[2], &mat[3]);
+      let (start_date_struct, end_date_struct) = Self::date_range(
+        |year| date_in_year(start_day_str, month_str, year),
+        |year| date_in_year(end_day_str, month_str, year),
+        None,
+        mat.get(4).and_then(|y| Self::explicit_year(y.as_str(), config)),
+        config,
+      ).map_err(bad_date)?;
+      Ok((Some(start_date_struct), Some(end_date_struct)))
+
+    } else if let Some(mat) = date_reg_arr[7].captures(normalized) {
+      let year = |i: usize| mat.get(i).and_then(|y| Self::explicit_year(y.as_str(), config));
+      let (start_date_struct, end_date_struct) = Self::date_range(
+        |year| date_in_year(&mat[2], &mat[1], year),
+        |year| date_in_year(&mat[5], &mat[4], year),
+        year(3),
+        year(6),
+        config,
+      ).map_err(bad_date)?;
+      Ok((Some(start_date_struct), Some(end_date_struct)))
 
-      let start_date_struct = Self::base_parse_date(start_date_str).map_err(|e| EventParseError{desc: e.to_string()})?;
-      let end_date_struct = Self::base_parse_date(end_date_str).map_err(|e| EventParseError{desc: e.to_string()})?;
-      return Ok((Some(start_date_struct), Some(end_date_struct)));
     } else {
-      Err(EventParseError { desc: "Bruh".to_owned() })
+      Err(EventParseError::bad_date(&datestr, None))
     }
   }
 }
@@ -393,6 +1222,8 @@ impl EventModel {
 mod tests {
 
   use crate::model::*;
+  use crate::config::YearPolicy;
+  use crate::locale::Locale;
 
   #[test]
   fn default_cons() {
@@ -491,40 +1322,291 @@ mod tests {
       }
     }
 
+    #[test]
+    fn test_time_variants() {
+      let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+      assert_eq!(EventModel::normalize_time("6pm\u{2013}10 p.m."), "6 PM-10 PM");
+      assert_eq!(EventModel::parse_time_tup("6\u{2013}10 PM"), Ok((time(18, 0), time(22, 0))));
+      assert_eq!(EventModel::parse_time_tup("6\u{2014}10pm"), Ok((time(18, 0), time(22, 0))));
+      assert_eq!(EventModel::parse_time_tup("7p"), Ok((time(19, 0), None)));
+      assert_eq!(EventModel::parse_time_tup("7:30 p.m."), Ok((time(19, 30), None)));
+      assert_eq!(EventModel::parse_time_tup("7:30pm"), Ok((time(19, 30), None)));
+      assert_eq!(EventModel::parse_time_tup("11am to 2pm"), Ok((time(11, 0), time(14, 0))));
+      assert_eq!(EventModel::parse_time_tup("8 PM until 11 PM"), Ok((time(20, 0), time(23, 0))));
+      assert_eq!(EventModel::parse_time_tup("6 PM - 10 PM"), Ok((time(18, 0), time(22, 0))));
+      assert_eq!(EventModel::parse_time_tup("18:00 to 24:00"), Ok((time(18, 0), time(0, 0))));
+      // The old `[A,P]M` took a comma for a marker
+      assert!(EventModel::parse_time_tup("6 ,M").is_err());
+
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (7p PT) () Zoned".to_string()).unwrap();
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-07-15T19:00:00-07:00");
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (10 p.m. to 2 a.m. CST) () Late".to_string()).unwrap();
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-07-16T02:00:00-05:00");
+
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+      assert_eq!(EventModel::parse_date_tup("24\u{2013}25 Feb 2024"), Ok((date(2024, 2, 24), date(2024, 2, 25))));
+      assert_eq!(EventModel::parse_date_tup("28 Feb to 2 Mar 2024"), Ok((date(2024, 2, 28), date(2024, 3, 2))));
+      assert_eq!(EventModel::parse_date_tup("2024-02-15 \u{2014} 2024-02-17"), Ok((date(2024, 2, 15), date(2024, 2, 17))));
+    }
+
+    #[test]
+    fn test_named_times() {
+      let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+      assert_eq!(EventModel::parse_time_tup("noon"), Ok((time(12, 0), None)));
+      assert_eq!(EventModel::parse_time_tup("Midnight"), Ok((time(0, 0), None)));
+      assert_eq!(EventModel::parse_time_tup("noon-3pm"), Ok((time(12, 0), time(15, 0))));
+
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (10pm-midnight) () Late".to_string()).unwrap();
+      assert_eq!(em.end_naive(), NaiveDate::from_ymd_opt(2024, 2, 16).unwrap().and_hms_opt(0, 0, 0));
+
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (Doors 6PM, Show 7PM) (White Oak) The Plot in You".to_string()).unwrap();
+      assert_eq!((em.doors(), em.show()), (time(18, 0), time(19, 0)));
+      assert_eq!((em.start_time(), em.end_time()), (time(18, 0), None));
+
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (show: 7-10pm; doors 6:30pm CST) () Beartooth".to_string()).unwrap();
+      assert_eq!((em.doors(), em.show()), (time(18, 30), time(19, 0)));
+      assert_eq!((em.start_time(), em.end_time()), (time(18, 30), time(22, 0)));
+      assert_eq!(em.timezone(), Some(Tz::America__Chicago));
+
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (Show 8PM) () Headliner".to_string()).unwrap();
+      assert_eq!((em.doors(), em.start_time()), (None, time(20, 0)));
+      assert!(serde_json::to_string(&em).unwrap().contains(r#""show":"08:00 pm""#));
+
+      let err = EventModel::from_line(r"- [ ] (15 Feb 2024) (Doors 6PM, 7PM) () Nope".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadTime { .. }));
+      // A bad time after a label keeps chrono's reason like any other bad time
+      let err = EventModel::from_line(r"- [ ] (15 Feb 2024) (Doors 13PM, Show 7PM) () Nope".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadTime { .. }));
+      assert!(std::error::Error::source(&err).is_some());
+
+      let tba = EventModel::from_line(r"- [ ] (15 Feb 2024) (TBA) () Secret Show".to_string()).unwrap();
+      assert!(tba.time_tba() && !tba.all_day());
+      assert_eq!(tba.start_time(), None);
+      assert!(serde_json::to_string(&tba).unwrap().contains(r#""time_tba":true"#));
+      let all_day = EventModel::from_line(r"- [ ] (15 Feb 2024) () () Festival".to_string()).unwrap();
+      assert!(!all_day.time_tba() && all_day.all_day());
+      assert!(!serde_json::to_string(&all_day).unwrap().contains("time_tba"));
+    }
+
+    #[test]
+    fn test_duration() {
+      let from_line = |line: &str| EventModel::from_line(line.to_string());
+      let at = |d, h, m| NaiveDate::from_ymd_opt(2024, 2, d).unwrap().and_hms_opt(h, m, 0);
+
+      let em = from_line(r"- [ ] (15 Feb 2024) (7PM +3h) () Show").unwrap();
+      assert_eq!(em.end_naive(), at(15, 22, 0));
+      assert_eq!(em.duration(), Some(Duration::hours(3)));
+      let em = from_line(r"- [ ] (15 Feb 2024) (7PM for 90m) () Show").unwrap();
+      assert_eq!(em.end_naive(), at(15, 20, 30));
+      let em = from_line(r"- [ ] (15 Feb 2024) (9AM, 2 days) () Workshop").unwrap();
+      assert_eq!(em.end_naive(), at(17, 9, 0));
+      let em = from_line(r"- [ ] (15 Feb 2024) (10PM for 1.5 hours CST) () Late").unwrap();
+      assert_eq!(em.end_naive(), at(15, 23, 30));
+      assert_eq!(em.timezone(), Some(Tz::America__Chicago));
+      let em = from_line(r"- [ ] (15 Feb 2024) (11PM CST +3h) () Later").unwrap();
+      assert_eq!(em.end_naive(), at(16, 2, 0));
+      assert!(!em.implicit_rollover());
+      let em = from_line(r"- [ ] (15 Feb 2024) (Doors 6PM, Show 7PM +2h) () Gig").unwrap();
+      assert_eq!((em.start_time(), em.end_naive()), (NaiveTime::from_hms_opt(18, 0, 0), at(15, 21, 0)));
+
+      // A duration needs a start and is the only way the end is given
+      assert!(from_line(r"- [ ] (15 Feb 2024) (7-9PM +3h) () Both").is_err());
+      assert!(from_line(r"- [ ] (15-16 Feb 2024) (7PM +3h) () Range").is_err());
+      assert!(from_line(r"- [ ] (15 Feb 2024) (TBA, 2h) () Unknown").is_err());
+      // Far too long to be real, and too long for Duration
+      let err = from_line(r"- [ ] (15 Feb 2024) (7PM +99999999999999999999h) () Forever").unwrap_err();
+      assert!(matches!(err, EventParseError::BadTime { .. }));
+      assert!(std::error::Error::source(&err).is_some());
+      assert!(from_line(r"- [ ] (15 Feb 2024) (7PM +400 days) () Year").is_err());
+
+      // Every other form has one too
+      assert_eq!(from_line(r"- [ ] (15 Feb 2024) () () Fest").unwrap().duration(), Some(Duration::days(1)));
+      assert_eq!(from_line(r"- [ ] (15-17 Feb 2024) () () Fest").unwrap().duration(), Some(Duration::days(3)));
+      assert_eq!(from_line(r"- [ ] (15 Feb 2024) (10PM-2AM) () Late").unwrap().duration(), Some(Duration::hours(4)));
+      assert_eq!(from_line(r"- [ ] (15 Feb 2024) (7PM) () Open").unwrap().duration(), None);
+      assert_eq!(from_line(r"- [ ] (15 Feb 2024) (TBA) () Secret").unwrap().duration(), None);
+      // 1 AM to 4 AM across spring forward is two real hours
+      assert_eq!(from_line(r"- [ ] (10 Mar 2024) (1-4AM America/Chicago) () Gap").unwrap().duration(), Some(Duration::hours(2)));
+    }
+
+    #[test]
+    fn test_parse_time_24h() {
+      let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+      assert_eq!(EventModel::parse_time_tup("18:00-22:30"), Ok((time(18, 0), time(22, 30))));
+      assert_eq!(EventModel::parse_time_tup("1930"), Ok((time(19, 30), None)));
+      assert_eq!(EventModel::parse_time_tup("0930 - 1100"), Ok((time(9, 30), time(11, 0))));
+      assert_eq!(EventModel::parse_time_tup("7:05"), Ok((time(7, 5), None)));
+      assert_eq!(EventModel::parse_time_tup("20:00-24:00"), Ok((time(20, 0), time(0, 0))));
+      assert!(matches!(EventModel::parse_time_tup("25:00"), Err(EventParseError::BadTime { source: Some(_), .. })));
+      assert!(matches!(EventModel::parse_time_tup("24:00"), Err(EventParseError::BadTime { .. })));
+      assert!(matches!(EventModel::parse_time_tup("20:00-24:30"), Err(EventParseError::BadTime { .. })));
+
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (20:00-24:00) () Late".to_string()).unwrap();
+      assert_eq!(em.end_naive(), NaiveDate::from_ymd_opt(2024, 2, 16).unwrap().and_hms_opt(0, 0, 0));
+      assert!(!em.implicit_rollover());
+
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (22:00-02:00 CET) () Overnight".to_string()).unwrap();
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-02-16T02:00:00+01:00");
+      assert!(em.implicit_rollover());
+    }
+
     #[test]
     fn test_parse_date_tup() {
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+      // Only matters for the year-less ones
+      let config = ParseConfig { reference_date: date(2023, 3, 1), ..Default::default() };
       let date_str_vec = vec![
-        "24-25 Feb",
-        "24-25 Feb 23",
-        "24-25 Feb 2023",
-        "24-25 Feb2023",
-        "24-25Feb2023",
-        "24Jan-25Feb2023",
-        "24Jan - 25Feb2023",
-        "24 Jan - 25 Feb 2023",
-        "25 Feb 2023",
-        "25Feb2023",
-        "25Feb",
-        "25Feb23",
-        "25Feb 23",
+        ("24-25 Feb", date(2023, 2, 24), date(2023, 2, 25)),
+        ("24-25 Feb 23", date(2023, 2, 24), date(2023, 2, 25)),
+        ("24-25 Feb 2023", date(2023, 2, 24), date(2023, 2, 25)),
+        ("24-25 Feb2023", date(2023, 2, 24), date(2023, 2, 25)),
+        ("24-25Feb2023", date(2023, 2, 24), date(2023, 2, 25)),
+        ("24Jan-25Feb2023", date(2023, 1, 24), date(2023, 2, 25)),
+        ("24Jan - 25Feb2023", date(2023, 1, 24), date(2023, 2, 25)),
+        ("24 Jan - 25 Feb 2023", date(2023, 1, 24), date(2023, 2, 25)),
+        ("24 Jan - 25 Feb 23", date(2023, 1, 24), date(2023, 2, 25)),
+        ("25 Feb 2023", date(2023, 2, 25), None),
+        ("25Feb2023", date(2023, 2, 25), None),
+        ("25Feb", date(2023, 2, 25), None),
+        ("25Feb23", date(2023, 2, 25), None),
+        ("25Feb 23", date(2023, 2, 25), None),
+        ("25 Feb 99", date(1999, 2, 25), None),
       ];
-      
-      let mut date_struct_vec = vec![];
-      for date_str in date_str_vec {
-        let temp = EventModel::parse_date_tup(date_str);
-        // dbg!(&temp);
-        assert!(temp.is_ok());
-        date_struct_vec.push(temp);
+
+      for (date_str, start, end) in date_str_vec {
+        let temp = EventModel::parse_date_tup_with(date_str, &config);
+        assert_eq!(temp, Ok((start, end)), "{date_str}");
       }
+
+      let config = ParseConfig { two_digit_pivot: Some(0), ..config };
+      assert_eq!(EventModel::parse_date_tup_with("25 Feb 23", &config), Ok((date(1923, 2, 25), None)));
+    }
+
+    #[test]
+    fn test_numeric_dates() {
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+      let config = ParseConfig { reference_date: date(2024, 1, 10), ..Default::default() };
+      let parse = |datestr: &str, config: &ParseConfig| EventModel::parse_date_tup_with(datestr, config);
+
+      assert_eq!(parse("2024-02-15", &config), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("2024-02-15 to 2024-02-17", &config), Ok((date(2024, 2, 15), date(2024, 2, 17))));
+      assert_eq!(parse("2024-12-30 - 2025-01-02", &config), Ok((date(2024, 12, 30), date(2025, 1, 2))));
+      assert_eq!(parse("15.02.2024", &config), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("2/15", &config), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("2/15/24", &config), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("2/15 - 2/17", &config), Ok((date(2024, 2, 15), date(2024, 2, 17))));
+      assert_eq!(parse("12/30 to 1/2/2025", &config), Ok((date(2024, 12, 30), date(2025, 1, 2))));
+
+      let day_first = ParseConfig { date_order: DateOrder::DayFirst, ..config.clone() };
+      assert_eq!(parse("2/3/2024", &config), Ok((date(2024, 2, 3), None)));
+      assert_eq!(parse("2/3/2024", &day_first), Ok((date(2024, 3, 2), None)));
+      // Dots don't care about the order
+      assert_eq!(parse("2.3.2024", &config), Ok((date(2024, 3, 2), None)));
+
+      assert!(matches!(parse("2024-02-30", &config), Err(EventParseError::BadDate { source: Some(_), .. })));
+      assert!(matches!(parse("15/2", &config), Err(EventParseError::BadDate { source: Some(_), .. })));
+      assert!(parse("15/2", &day_first).is_ok());
+    }
+
+    #[test]
+    fn test_month_first_dates() {
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+      let config = ParseConfig { reference_date: date(2024, 1, 10), ..Default::default() };
+      let parse = |datestr: &str| EventModel::parse_date_tup_with(datestr, &config);
+
+      assert_eq!(parse("Nov 21"), Ok((date(2023, 11, 21), None)));
+      assert_eq!(parse("nov 21st"), Ok((date(2023, 11, 21), None)));
+      assert_eq!(parse("Nov 21, 2024"), Ok((date(2024, 11, 21), None)));
+      assert_eq!(parse("Nov 21-22, 2024"), Ok((date(2024, 11, 21), date(2024, 11, 22))));
+      assert_eq!(parse("Feb 24th - Mar 2nd"), Ok((date(2024, 2, 24), date(2024, 3, 2))));
+      assert_eq!(parse("Dec 28 - Jan 2, 2025"), Ok((date(2024, 12, 28), date(2025, 1, 2))));
+      assert_eq!(parse("Dec 30, 2024 \u{2013} Jan 2, 2025"), Ok((date(2024, 12, 30), date(2025, 1, 2))));
+      // Ordinals and commas work day first too
+      assert_eq!(parse("21st Nov, 2024"), Ok((date(2024, 11, 21), None)));
+      assert_eq!(parse("24th-25th Feb"), Ok((date(2024, 2, 24), date(2024, 2, 25))));
+
+      assert!(matches!(parse("Nov 2024"), Err(EventParseError::BadDate { source: None, .. })));
+      assert!(matches!(parse("Feb 30"), Err(EventParseError::BadDate { source: Some(_), .. })));
+    }
+
+    #[test]
+    fn test_localized_dates() {
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+      let parse = |datestr: &str, locale| {
+        let config = ParseConfig { locale, reference_date: date(2024, 1, 10), ..Default::default() };
+        EventModel::parse_date_tup_with(datestr, &config)
+      };
+
+      assert_eq!(parse("3 März", Locale::De), Ok((date(2024, 3, 3), None)));
+      assert_eq!(parse("3. März 2024", Locale::De), Ok((date(2024, 3, 3), None)));
+      assert_eq!(parse("Samstag, 15. Feb", Locale::De), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("28 Dez - 2 Jan", Locale::De), Ok((date(2023, 12, 28), date(2024, 1, 2))));
+      assert_eq!(parse("21 noviembre", Locale::Es), Ok((date(2023, 11, 21), None)));
+      assert_eq!(parse("15 feb", Locale::Es), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("1º-3 dic 2024", Locale::Es), Ok((date(2024, 12, 1), date(2024, 12, 3))));
+      assert_eq!(parse("1er févr. 2024", Locale::Fr), Ok((date(2024, 2, 1), None)));
+      assert_eq!(parse("sexta-feira, 15 fevereiro 2024", Locale::Pt), Ok((date(2024, 2, 15), None)));
+      // `mar.` is Tuesday in French and `mar` March in Spanish, the weekday only goes when the date reads without it
+      assert_eq!(parse("mar. 15 févr. 2024", Locale::Fr), Ok((date(2024, 2, 15), None)));
+      assert_eq!(parse("mar 15", Locale::Es), Ok((date(2024, 3, 15), None)));
+      assert_eq!(parse("mar 12 mar 2024", Locale::Es), Ok((date(2024, 3, 12), None)));
+      // Full English names never needed a locale
+      assert_eq!(parse("21 November 2024", Locale::En), Ok((date(2024, 11, 21), None)));
+      assert_eq!(parse("Sat, Nov 23", Locale::En), Ok((date(2023, 11, 23), None)));
+      assert!(matches!(parse("3 März", Locale::En), Err(EventParseError::BadDate { source: Some(_), .. })));
+    }
+
+    #[test]
+    fn test_year_inference() {
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+      let parse = |datestr: &str, config: &ParseConfig| EventModel::parse_date_tup_with(datestr, config).unwrap();
+      let config = |year_policy, reference| ParseConfig { year_policy, reference_date: Some(reference), ..Default::default() };
+
+      // Re-parsing in January shouldn't push last November's shows into the new year
+      let january = config(YearPolicy::Nearest, date(2024, 1, 10));
+      assert_eq!(parse("21 Nov", &january), (Some(date(2023, 11, 21)), None));
+      assert_eq!(parse("15 Feb", &january), (Some(date(2024, 2, 15)), None));
+
+      let next = config(YearPolicy::Next, date(2024, 1, 10));
+      assert_eq!(parse("21 Nov", &next), (Some(date(2024, 11, 21)), None));
+      assert_eq!(parse("5 Jan", &next), (Some(date(2025, 1, 5)), None));
+      assert_eq!(parse("29 Feb", &config(YearPolicy::Next, date(2024, 3, 1))), (Some(date(2028, 2, 29)), None));
+
+      let fixed = config(YearPolicy::Fixed(2019), date(2024, 1, 10));
+      assert_eq!(parse("24-25 Feb", &fixed), (Some(date(2019, 2, 24)), Some(date(2019, 2, 25))));
+
+      let note = ParseConfig { year_policy: YearPolicy::NoteCreated, note_created: Some(date(2022, 6, 1)), ..january.clone() };
+      assert_eq!(parse("21 Nov", &note), (Some(date(2022, 11, 21)), None));
+
+      // A written year always wins
+      assert_eq!(parse("21 Nov 2030", &next), (Some(date(2030, 11, 21)), None));
+    }
+
+    #[test]
+    fn test_year_boundary_range() {
+      let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+      let config = ParseConfig { reference_date: Some(date(2024, 12, 1)), ..Default::default() };
+      let parse = |datestr: &str| EventModel::parse_date_tup_with(datestr, &config).unwrap();
+
+      assert_eq!(parse("28 Dec - 2 Jan"), (Some(date(2024, 12, 28)), Some(date(2025, 1, 2))));
+      assert_eq!(parse("28 Dec - 2 Jan 2025"), (Some(date(2024, 12, 28)), Some(date(2025, 1, 2))));
+      assert_eq!(parse("24 Jan - 25 Feb 2023"), (Some(date(2023, 1, 24)), Some(date(2023, 2, 25))));
+      assert_eq!(parse("Dec 28 - Jan 2"), (Some(date(2024, 12, 28)), Some(date(2025, 1, 2))));
+      assert_eq!(parse("12/28 - 1/2/2025"), (Some(date(2024, 12, 28)), Some(date(2025, 1, 2))));
+
+      // Only short ranges that really cross New Year wrap, the rest stay backwards for lint to report
+      assert_eq!(parse("28 Feb - 2 Jan 2024"), (Some(date(2024, 2, 28)), Some(date(2024, 1, 2))));
+      assert_eq!(parse("Mar 5 - Feb 3"), (Some(date(2025, 3, 5)), Some(date(2025, 2, 3))));
+      assert_eq!(parse("3/5 - 2/3/2024"), (Some(date(2024, 3, 5)), Some(date(2024, 2, 3))));
     }
 
     #[test]
     fn test_time_regex_arr() {
       /* I'm manually copy + pasting the regex arr bc I don't know how to make regex a const in EventModel */
       let time_reg_arr: [Regex; 3] = [
-        Regex::new(r"^\d{1,2}:\d\d [A,P]M").unwrap(),
-        Regex::new(r"^\d{1,2} [A,P]M").unwrap(),
-        Regex::new(r"^\d{1,2}(:\d\d)?-\d{1,2}(:\d\d)? [A,P]M").unwrap(),
+        Regex::new(r"^\d{1,2}:\d\d [AP]M").unwrap(),
+        Regex::new(r"^\d{1,2} [AP]M").unwrap(),
+        Regex::new(r"^\d{1,2}(:\d\d)?-\d{1,2}(:\d\d)? [AP]M").unwrap(),
       ];
       let time1: String = "6:00 AM".to_owned();
       let time2: String = "6 PM".to_owned();
@@ -619,6 +1701,190 @@ mod tests {
       }
     }
 
+    #[test]
+    fn test_from_line_errors() {
+      use std::error::Error;
+
+      let line = r"- [ ] (15 Feb 2024) (25 PM) (White Oak Music Hall, Houston) The Plot in You";
+      let err = EventModel::from_line(line.to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadTime { .. }));
+      assert_eq!(&line[err.span().unwrap()], "25 PM");
+      assert_eq!(err.loc().column(line), Some(22));
+      assert_eq!(err.loc().end_column(line), Some(27));
+      assert!(err.source().is_some());
+      assert!(err.to_string().starts_with("invalid time `25 PM`"));
+
+      let err = EventModel::from_line(r"- [ ] (15 Feb 2024) (6ish) () Soon".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadTime { source: None, .. }));
+
+      let line = r"- [ ] (31 Feb 2024) () () Nope";
+      let err = EventModel::from_line(line.to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadDate { source: Some(_), .. }));
+      assert_eq!(&line[err.span().unwrap()], "31 Feb 2024");
+
+      let line = r"- [ ] (Feb) () () Nope";
+      let err = EventModel::from_line(line.to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadDate { source: None, .. }));
+      assert_eq!(err.span(), Some(7..10));
+
+      let err = EventModel::from_line(r"- [ ] (2 Nov) () (Houston) ".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::EmptyTitle { .. }));
+
+      let err = EventModel::from_line(r"- [ ] (2 Nov) (Houston) Polyphia".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::NotEvent { .. }));
+      assert_eq!(err.line(), None);
+      assert_eq!(err.with_line(12).line(), Some(12));
+    }
+
+    #[test]
+    fn test_timezone_suffix() {
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (6-10PM CST) (White Oak Music Hall, Houston) The Plot in You".to_string()).unwrap();
+      assert_eq!(em.timezone(), Some(Tz::America__Chicago));
+      assert_eq!(em.start_time(), NaiveTime::from_hms_opt(18, 0, 0));
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-02-15T18:00:00-06:00");
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-02-15T22:00:00-06:00");
+
+      // CST written in the summer still means Chicago, so it comes out as CDT
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (6 PM CST) () Summer".to_string()).unwrap();
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-07-15T18:00:00-05:00");
+
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (6 PM Europe/Berlin) () Berlin".to_string()).unwrap();
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-07-15T18:00:00+02:00");
+      assert!(serde_json::to_string(&em).unwrap().contains(r#""timezone":"Europe/Berlin""#));
+
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (6 PM) () Floating".to_string()).unwrap();
+      assert_eq!(em.timezone(), None);
+      assert_eq!(em.start_datetime(), None);
+
+      let config = ParseConfig { default_tz: Some(Tz::America__New_York), ..Default::default() };
+      let em = EventModel::from_line_with(r"- [ ] (15 Jul 2024) (6 PM) () Default".to_string(), &config).unwrap();
+      assert_eq!(em.timezone(), Some(Tz::America__New_York));
+      let em = EventModel::from_line_with(r"- [ ] (15 Jul 2024) (6 PM PT) () Explicit".to_string(), &config).unwrap();
+      assert_eq!(em.timezone(), Some(Tz::America__Los_Angeles));
+    }
+
+    #[test]
+    fn test_overnight() {
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (10PM-2AM) (White Oak) Late Show".to_string()).unwrap();
+      assert_eq!(em.start_date(), NaiveDate::from_ymd_opt(2024, 2, 15).unwrap());
+      assert_eq!(em.end_date(), NaiveDate::from_ymd_opt(2024, 2, 16).unwrap());
+      assert_eq!(em.end_naive(), NaiveDate::from_ymd_opt(2024, 2, 16).unwrap().and_hms_opt(2, 0, 0));
+      assert!(em.implicit_rollover());
+
+      // A date range already says which day it ends on
+      let em = EventModel::from_line(r"- [ ] (28-29 Oct 2023) (10PM-2AM) (Austin) Freaky Deaky".to_string()).unwrap();
+      assert_eq!(em.end_date(), NaiveDate::from_ymd_opt(2023, 10, 29).unwrap());
+      assert!(!em.implicit_rollover());
+
+      // 25 hours, the heuristic can't know that
+      let em = EventModel::from_line(r"- [ ] (15 Feb 2024) (6PM-7PM +1) () Marathon".to_string()).unwrap();
+      assert_eq!(em.end_date(), NaiveDate::from_ymd_opt(2024, 2, 16).unwrap());
+      assert!(!em.implicit_rollover());
+
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (10PM-2AM +1 CST) () Zoned".to_string()).unwrap();
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-07-16T02:00:00-05:00");
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (10PM-2AM CST +1) () Zoned".to_string()).unwrap();
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-07-16T02:00:00-05:00");
+
+      // Zones with a sign in their name keep it
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (6-10PM Etc/GMT+5) () Signed".to_string()).unwrap();
+      assert_eq!(em.timezone(), Some(Tz::Etc__GMTPlus5));
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-07-15T22:00:00-05:00");
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (6 PM Etc/GMT-3) () Signed".to_string()).unwrap();
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-07-15T18:00:00+03:00");
+      let em = EventModel::from_line(r"- [ ] (15 Jul 2024) (10PM-2AM Etc/GMT+5 +1) () Signed".to_string()).unwrap();
+      assert_eq!(em.end_datetime().unwrap().to_rfc3339(), "2024-07-16T02:00:00-05:00");
+
+      let err = EventModel::from_line(r"- [ ] (15 Feb 2024) (6 PM +1) () No end".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::BadTime { .. }));
+    }
+
+    #[test]
+    fn test_timezone_dst() {
+      // 2:30 AM doesn't exist on spring-forward day
+      let em = EventModel::from_line(r"- [ ] (10 Mar 2024) (2:30 AM America/Chicago) () Gap".to_string()).unwrap();
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-03-10T03:30:00-05:00");
+      // 1:30 AM happens twice on fall-back day, take the first
+      let em = EventModel::from_line(r"- [ ] (3 Nov 2024) (1:30 AM America/Chicago) () Overlap".to_string()).unwrap();
+      assert_eq!(em.start_datetime().unwrap().to_rfc3339(), "2024-11-03T01:30:00-05:00");
+    }
+
+    #[test]
+    fn test_status() {
+      let status = |line: &str| EventModel::from_line(line.to_string()).map(|em| em.status());
+      assert_eq!(status(r"- [ ] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Open));
+      assert_eq!(status(r"- [x] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Done));
+      assert_eq!(status(r"- [X] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Done));
+      assert_eq!(status(r"- [-] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Cancelled));
+      assert_eq!(status(r"- [>] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Deferred));
+      assert_eq!(status(r"- [?] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Tentative));
+      assert_eq!(status(r"- [!] (2 Nov 2023) () (Houston) Polyphia"), Ok(EventStatus::Important));
+      // The old `[ ,x]` let a comma through
+      assert!(matches!(status(r"- [,] (2 Nov 2023) () (Houston) Polyphia"), Err(EventParseError::NotEvent { .. })));
+
+      let json = serde_json::to_string(&EventModel::from_line(r"- [-] (2 Nov 2023) () (Houston) Polyphia".to_string()).unwrap()).unwrap();
+      assert!(json.contains(r#""status":"cancelled""#));
+      // Checking an event off doesn't make it a different event
+      assert_eq!(
+        EventModel::from_line(r"- [x] (2 Nov 2023) () (Houston) Polyphia".to_string()).unwrap().uid(),
+        EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) Polyphia".to_string()).unwrap().uid(),
+      );
+    }
+
+    #[test]
+    fn test_strikethrough() {
+      let em = EventModel::from_line(r"- [x] (2 Nov 2023) () (Houston) ~~Polyphia~~".to_string()).unwrap();
+      assert_eq!(em.title(), "Polyphia");
+      assert!(em.crossed_out());
+      assert!(serde_json::to_string(&em).unwrap().contains(r#""crossed_out":true"#));
+
+      let em = EventModel::from_line(r"- [ ] ~~(2 Nov 2023) () (Houston) Polyphia~~".to_string()).unwrap();
+      assert_eq!((em.title(), em.crossed_out(), em.place()), ("Polyphia", true, "Houston"));
+      let em = EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) ~~Polyphia~~ ^polyphia-23".to_string()).unwrap();
+      assert_eq!((em.title(), em.crossed_out()), ("Polyphia", true));
+      assert_eq!(em.uid(), EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) Polyphia ^polyphia-23".to_string()).unwrap().uid());
+
+      let em = EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) Polyphia ~~w/ Covet~~".to_string()).unwrap();
+      assert_eq!((em.title(), em.crossed_out()), ("Polyphia ~~w/ Covet~~", false));
+      let em = EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) Polyphia".to_string()).unwrap();
+      assert!(!em.crossed_out());
+      assert!(!serde_json::to_string(&em).unwrap().contains("crossed_out"));
+
+      let err = EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) ~~~~".to_string()).unwrap_err();
+      assert!(matches!(err, EventParseError::EmptyTitle { .. }));
+    }
+
+    #[test]
+    fn test_uid_stable() {
+      let original = EventModel::from_line(r"- [ ] (15 Feb 2024) (6-10PM) (White Oak Music Hal, Houston) The Plot in You & Beartooth".to_string()).unwrap();
+      let edited = EventModel::from_line(r"- [ ] (15 Feb 2024) (7-11PM) (White Oak Music Hall, Houston) The Plot In You & Beartooth".to_string()).unwrap();
+      let other = EventModel::from_line(r"- [ ] (16 Feb 2024) (6-10PM) (White Oak Music Hall, Houston) The Plot in You & Beartooth".to_string()).unwrap();
+
+      assert_eq!(original.derive_uid("Concerts.md"), edited.derive_uid("Concerts.md"));
+      assert_ne!(original.derive_uid("Concerts.md"), other.derive_uid("Concerts.md"));
+      assert_ne!(original.derive_uid("Concerts.md"), original.derive_uid("Other.md"));
+      // Pinned so an accidental change to the hash input shows up here, not as duplicated calendar events
+      assert_eq!(original.derive_uid("Concerts.md"), "375ddb9299cd1d51@md-event-parser");
+    }
+
+    #[test]
+    fn test_uid_override() {
+      let block = EventModel::from_line(r"- [ ] (2 Nov 2023) () (Houston) Polyphia ^polyphia-23".to_string()).unwrap();
+      assert_eq!(block.title(), "Polyphia");
+      let renamed = EventModel::from_line(r"- [ ] (3 Nov 2023) () (Houston) Polyphia w/ Covet ^polyphia-23".to_string()).unwrap();
+      assert_eq!(block.derive_uid("Concerts.md"), renamed.derive_uid("Concerts.md"));
+      assert_ne!(block.derive_uid("Concerts.md"), block.derive_uid("Other.md"));
+
+      let global = EventModel::from_line(r"- [ ] (2 Nov 2023) () (Club (downstairs)) Polyphia live (id:polyphia-2023)".to_string()).unwrap();
+      assert_eq!(global.place(), "Club (downstairs)");
+      assert_eq!(global.title(), "Polyphia live");
+      assert_eq!(global.derive_uid("Concerts.md"), "polyphia-2023@md-event-parser");
+      assert_eq!(global.derive_uid("Concerts.md"), global.derive_uid("Other.md"));
+
+      let json = serde_json::to_string(&global).unwrap();
+      assert!(json.contains(r#""uid":"polyphia-2023@md-event-parser""#));
+    }
+
 
 
 }
\ No newline at end of file
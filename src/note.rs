@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::locale::Locale;
+use crate::parsing;
+
+// Zettelkasten ids are the creation time, ex 202310152010 is 2023-10-15 20:10
+pub const ZETTELREG: &str = r"^(\d{12}|\d{14})$";
+pub const FILENAMEREG: &str = r"(\d{14}|\d{12}|\d{4}-\d{2}-\d{2})";
+pub const CREATEDREG: &str = r#"^created: *["']?([^"']*?)["']?\s*$"#;
+
+const ZETTEL_FMTS: [&str; 2] = ["%Y%m%d%H%M%S", "%Y%m%d%H%M"];
+const CREATED_FMTS: [&str; 4] = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CreatedFrom {
+  Frontmatter,
+  Id,
+  Filename,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NoteMeta {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub id: Option<String>,
+
+  #[serde(serialize_with = "serialize_created", skip_serializing_if = "Option::is_none")]
+  pub created: Option<NaiveDateTime>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub created_from: Option<CreatedFrom>, // Which of the three places `created` came out of
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tagged_by: Option<parsing::TagRule>, // Which header rule made this an event note, set by the vault scan
+
+  #[serde(skip)]
+  pub timezone: Option<Tz>, // `Timezone:` header line
+
+  #[serde(skip)]
+  pub locale: Option<Locale>, // `lang:` in the frontmatter or a `Lang:` header line
+}
+
+fn serialize_created<S: serde::Serializer>(created: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+  match created {
+    Some(created) => serializer.serialize_str(&created.format("%Y-%m-%dT%H:%M:%S").to_string()),
+    _ => unreachable!(),
+  }
+}
+
+fn parse_zettel(id: &str) -> Option<NaiveDateTime> {
+  ZETTEL_FMTS.iter().find_map(|fmt| NaiveDateTime::parse_from_str(id, fmt).ok())
+}
+
+fn parse_created(value: &str) -> Option<NaiveDateTime> {
+  CREATED_FMTS.iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+    .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+}
+
+impl NoteMeta {
+  pub fn parse(path: Option<&Path>, filestr: &str) -> NoteMeta {
+    /*
+     * Where the creation time comes from, most explicit first:
+     * 1. frontmatter `created: 2023-10-15 20:10`
+     * 2. a Zettelkasten id on the first line of the body, like res/example.md
+     * 3. an id or a YYYY-MM-DD date in the file name, ex `202310152010 Concerts.md` or a daily note
+     */
+    let mut meta = NoteMeta { timezone: parsing::note_timezone(filestr), locale: parsing::note_locale(filestr), ..Default::default() };
+    let mut lines = filestr.lines().peekable();
+
+    if lines.peek().map(|l| l.trim_end()) == Some("---") {
+      lines.next();
+      let created_reg = Regex::new(CREATEDREG).unwrap();
+      for line in lines.by_ref() {
+        if matches!(line.trim_end(), "---" | "...") {
+          break;
+        }
+        if let Some(caps) = created_reg.captures(line) {
+          meta.created = parse_created(&caps[1]);
+        }
+      }
+      if meta.created.is_some() {
+        meta.created_from = Some(CreatedFrom::Frontmatter);
+      }
+    }
+
+    let zettel_reg = Regex::new(ZETTELREG).unwrap();
+    if let Some(id) = lines.find(|l| !l.trim().is_empty()).map(str::trim).filter(|l| zettel_reg.is_match(l)) {
+      meta.id = Some(id.to_string());
+      if meta.created.is_none() {
+        meta.created = parse_zettel(id);
+        meta.created_from = meta.created.map(|_| CreatedFrom::Id);
+      }
+    }
+
+    let stem = path.and_then(Path::file_stem).map(|s| s.to_string_lossy().into_owned());
+    let filename_reg = Regex::new(FILENAMEREG).unwrap();
+    if let Some(found) = stem.as_deref().and_then(|s| filename_reg.find(s)).map(|m| m.as_str()) {
+      let created = parse_zettel(found).or_else(|| parse_created(found));
+      if meta.id.is_none() && !found.contains('-') {
+        meta.id = Some(found.to_string());
+      }
+      if meta.created.is_none() && created.is_some() {
+        meta.created = created;
+        meta.created_from = Some(CreatedFrom::Filename);
+      }
+    }
+
+    meta
+  }
+
+  pub fn created_date(&self) -> Option<NaiveDate> {
+    self.created.map(|c| c.date())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::note::*;
+
+  #[test]
+  fn test_zettel_header() {
+    let filestr = std::fs::read_to_string("res/example.md").unwrap();
+    let meta = NoteMeta::parse(Some(Path::new("res/example.md")), &filestr);
+    assert_eq!(meta.id.as_deref(), Some("202310152010"));
+    assert_eq!(meta.created, NaiveDate::from_ymd_opt(2023, 10, 15).unwrap().and_hms_opt(20, 10, 0));
+    assert_eq!(meta.created_from, Some(CreatedFrom::Id));
+  }
+
+  #[test]
+  fn test_frontmatter_wins() {
+    let filestr = "---\ntags: [event]\ncreated: \"2022-01-05 09:30\"\n---\n202310152010\nTags: #event\n";
+    let meta = NoteMeta::parse(Some(Path::new("Concerts.md")), filestr);
+    assert_eq!(meta.id.as_deref(), Some("202310152010"));
+    assert_eq!(meta.created, NaiveDate::from_ymd_opt(2022, 1, 5).unwrap().and_hms_opt(9, 30, 0));
+    assert_eq!(meta.created_from, Some(CreatedFrom::Frontmatter));
+  }
+
+  #[test]
+  fn test_filename() {
+    let meta = NoteMeta::parse(Some(Path::new("vault/202301021530 Concerts.md")), "Tags: #event\n");
+    assert_eq!(meta.id.as_deref(), Some("202301021530"));
+    assert_eq!(meta.created_from, Some(CreatedFrom::Filename));
+
+    let meta = NoteMeta::parse(Some(Path::new("Daily/2023-06-01.md")), "Tags: #event\n");
+    assert_eq!(meta.id, None);
+    assert_eq!(meta.created_date(), NaiveDate::from_ymd_opt(2023, 6, 1));
+
+    let meta = NoteMeta::parse(Some(Path::new("Concerts.md")), "Tags: #event\n");
+    assert_eq!(meta, NoteMeta::default());
+  }
+}
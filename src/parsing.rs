@@ -3,27 +3,283 @@ use std::fs::File;
 use regex::Regex;
 
 use std::env;
-use std::io::prelude::*;
+use std::sync::LazyLock;
+use std::io::{self, prelude::*};
 
-pub const EVENTREGEX : &'static str = r"- \[[ ,x]\] +\((.*)\) +\((.*)\) +\((.*)\) ?(.*)";
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::config::{self, ParseConfig};
+use crate::locale::Locale;
+use crate::markdown;
+use crate::model::{EventModel, EventParseError};
+use crate::note::NoteMeta;
+
+// The first group is the checkbox, see EventStatus for what each state means. `~~` can cross out the whole rest of the line.
+// Any CommonMark list marker (`-`, `*`, `+`, `1.`, `1)`) works, indented and inside any number of `>` quotes or callouts
+pub const EVENTREGEX : &str = r"^(?:[ \t]*>)*[ \t]*(?:[-*+]|\d{1,9}[.)])[ \t]+\[([ xX\->?!])\] +(?:~~)?\((.*)\) +\((.*)\) +\((.*)\) ?(.*)";
+// A `(id:...)` at the end of the line, optionally before a block id. It's kept out of the match so the greedy
+// groups don't swallow it into the place, the title still runs to the end of the line and gets it back
+pub const TRAILINGIDREG: &str = r"\s*\(id: *[^)\s]+ *\)(?:\s*\^[A-Za-z0-9-]+)?\s*$";
+
+pub fn event_captures(linestr: &str) -> Option<regex::Captures<'_>> {
+  static TRAILING_ID_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(TRAILINGIDREG).unwrap());
+  static EVENT_REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(EVENTREGEX).unwrap());
+  let end = TRAILING_ID_REG.find(linestr).map_or(linestr.len(), |m| m.start());
+  EVENT_REG.captures(&linestr[..end])
+}
+
+// What a note's header can say to mark it as holding events, `tag` being `event` unless configured otherwise:
+// `tags: [event]` or a `tags:` list in the frontmatter, a `Tags: #event` line, or `#event` in the first heading's block.
+// Nested tags count too, `#event/concert` is an event note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TagRule {
+  Frontmatter,
+  TagsLine,
+  Heading,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagMatch {
+  pub rule: TagRule,
+  pub line: usize, // 1-based
+}
+
+pub const FRONTMATTERTAGSREG: &str = r"(?i)^tags?:\s*(.*?)\s*$";
+pub const YAMLITEMREG: &str = r"^\s*-\s+(.*?)\s*$";
+pub const TAGSLINEREG: &str = r"(?i)^tags:";
+pub const HEADINGREG: &str = r"^#{1,6}(?:\s|$)";
+// How many lines after the frontmatter count as the header
+const HEADER_LINES: usize = 30;
+
+fn tag_reg(tag: &str) -> Regex {
+  /* `#tag` or a nested `#tag/...`, but not `#tags` or `##tag` */
+  Regex::new(&format!(r"(?i)(?:^|[^#\w])#{}(?:/|[^\p{{L}}\p{{N}}_-]|$)", regex::escape(tag))).unwrap()
+}
+
+fn yaml_tag_is(value: &str, tag: &str) -> bool {
+  let name = value.trim().trim_matches(['"', '\'']).trim_start_matches('#').to_lowercase();
+  let tag = tag.to_lowercase();
+  name == tag || name.strip_prefix(&tag).is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderPart {
+  Frontmatter, // Including the `---` lines around it
+  Lead, // Between the frontmatter and the first heading
+  Heading, // The first heading and the lines under it up to a blank line
+  Rest, // Anything after that, up to HEADER_LINES lines past the frontmatter
+}
+
+// Where a note's header is, fed one line at a time so it works on a stream as well as a whole note
+#[derive(Debug)]
+pub struct Header {
+  part: HeaderPart,
+  lines: usize,
+  body_lines: usize,
+  heading_reg: Regex,
+}
+
+impl Header {
+  pub fn new() -> Header {
+    Header { part: HeaderPart::Lead, lines: 0, body_lines: 0, heading_reg: Regex::new(HEADINGREG).unwrap() }
+  }
+
+  pub fn part_of(&mut self, linestr: &str) -> Option<HeaderPart> {
+    /* Which part of the header the next line of the note is in, None once the header is over */
+    self.lines += 1;
+    match self.part {
+      HeaderPart::Lead if self.lines == 1 && linestr.trim_end() == "---" => {
+        self.part = HeaderPart::Frontmatter;
+        return Some(HeaderPart::Frontmatter);
+      },
+      HeaderPart::Frontmatter => {
+        if matches!(linestr.trim_end(), "---" | "...") {
+          self.part = HeaderPart::Lead;
+        }
+        return Some(HeaderPart::Frontmatter);
+      },
+      _ => {},
+    }
+
+    self.body_lines += 1;
+    if self.body_lines > HEADER_LINES {
+      return None;
+    }
+    self.part = match self.part {
+      HeaderPart::Lead if self.heading_reg.is_match(linestr) => HeaderPart::Heading,
+      HeaderPart::Heading if linestr.trim().is_empty() => HeaderPart::Rest,
+      part => part,
+    };
+    Some(self.part)
+  }
+}
+
+pub fn header_lines(filestr: &str) -> impl Iterator<Item = &str> {
+  let mut header = Header::new();
+  filestr.lines().map_while(move |line| header.part_of(line).map(|_| line))
+}
+
+pub fn event_tag_in<R: BufRead>(reader: R, tag: &str) -> io::Result<Option<TagMatch>> {
+  /*
+   * Which rule, if any, marks this note as an event note. Only the header is read, the frontmatter and the
+   * HEADER_LINES lines after it, so big notes cost no more than small ones as long as they're given as a BufReader.
+   */
+  let tag_reg = tag_reg(tag);
+  let key_reg = Regex::new(FRONTMATTERTAGSREG).unwrap();
+  let item_reg = Regex::new(YAMLITEMREG).unwrap();
+  let tags_line_reg = Regex::new(TAGSLINEREG).unwrap();
+
+  let mut header = Header::new();
+  let mut in_tags_list = false;
+  for (idx, line) in reader.lines().enumerate() {
+    let line = line?;
+    let found = |rule| Ok(Some(TagMatch { rule, line: idx + 1 }));
+
+    match header.part_of(&line) {
+      None => break,
+      Some(HeaderPart::Frontmatter) => {
+        if let Some(caps) = item_reg.captures(&line).filter(|_| in_tags_list) {
+          if yaml_tag_is(&caps[1], tag) {
+            return found(TagRule::Frontmatter);
+          }
+          continue;
+        }
+        let value = key_reg.captures(&line).map(|caps| caps[1].to_string());
+        // `tags: [event, music]`, `tags: event, music` or `tags: event music`
+        if value.as_deref().is_some_and(|v| v.split(|c: char| "[],".contains(c) || c.is_whitespace()).any(|t| yaml_tag_is(t, tag))) {
+          return found(TagRule::Frontmatter);
+        }
+        in_tags_list = value.is_some_and(|v| v.is_empty());
+      },
+      Some(part) => {
+        if tags_line_reg.is_match(&line) && tag_reg.is_match(&line) {
+          return found(TagRule::TagsLine);
+        }
+        if part == HeaderPart::Heading && tag_reg.is_match(&line) {
+          return found(TagRule::Heading);
+        }
+      },
+    }
+  }
+  Ok(None)
+}
 
 pub fn file_is_event(filestr: &str) -> bool {
-  /* Need to, later, figure out how to only check the first like, 10 lines so I don't process entire,
-  large files, since it'll always be at the beginning */
-  let event_bool: bool = filestr.lines().any(|line| line.matches("Tags: #event").collect::<Vec<&str>>().len() > 0);
-  event_bool
+  file_is_event_with(filestr, &ParseConfig::default())
+}
+
+pub fn file_is_event_with(filestr: &str, config: &ParseConfig) -> bool {
+  event_tag_in(filestr.as_bytes(), config.event_tag()).ok().flatten().is_some()
+}
+
+// The part of a line in front of any list item, quotes in group 1 and the indent before the marker in group 2
+pub const LISTITEMREG: &str = r"^((?:[ \t]*>)*)([ \t]*)(?:[-*+]|\d{1,9}[.)])(?:[ \t]|$)";
+const TAB_WIDTH: usize = 4;
+
+pub fn list_prefix(linestr: &str) -> Option<(usize, usize)> {
+  /* (blockquote depth, indent in columns) of a list item, None if the line isn't one */
+  static REG: LazyLock<Regex> = LazyLock::new(|| Regex::new(LISTITEMREG).unwrap());
+  let caps = REG.captures(linestr)?;
+  let quotes = caps[1].matches('>').count();
+  let indent = caps[2].chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum();
+  Some((quotes, indent))
+}
+
+// Tracks how deep each list item is, by the indents of the items above it. Indents alone can't say it since
+// one note nests by two spaces and the next by a tab
+#[derive(Debug, Default)]
+struct ListNesting {
+  quotes: usize,
+  indents: Vec<usize>,
+}
+
+impl ListNesting {
+  fn depth(&mut self, linestr: &str) -> Option<usize> {
+    /* The item's depth, 0 for top level, None for lines that aren't list items */
+    let Some((quotes, indent)) = list_prefix(linestr) else {
+      // A paragraph or heading back at the margin ends the list, blank lines and continuation lines don't
+      if linestr.starts_with(|c: char| !c.is_whitespace()) {
+        self.indents.clear();
+      }
+      return None;
+    };
+    if quotes != self.quotes {
+      self.quotes = quotes;
+      self.indents.clear();
+    }
+    while self.indents.last().is_some_and(|&top| top >= indent) {
+      self.indents.pop();
+    }
+    self.indents.push(indent);
+    Some(self.indents.len() - 1)
+  }
 }
 
 pub fn line_is_event(linestr: &str) -> bool {
   /* For now, only really checking the beginning of the line for `- [ ] (.*) (.*) (.*)` */
-  let reg: Regex = Regex::new(EVENTREGEX).expect("Bruh");
-  reg.captures(linestr.trim()).is_some()
+  event_captures(linestr.trim()).is_some()
+}
+
+// Note-level zone, written next to `Tags: #event` like `Timezone: America/Chicago`
+pub const TZLINEREG: &str = r"(?i)^\s*(?:timezone|tz): *(\S+)\s*$";
+
+// Obsidian's own frontmatter key is `lang`, a body line like `Lang: de` works the same as `Timezone:`
+pub const LANGLINEREG: &str = r"(?i)^\s*(?:lang|language|locale): *(\S+)\s*$";
+
+pub fn note_locale(filestr: &str) -> Option<Locale> {
+  let reg = Regex::new(LANGLINEREG).unwrap();
+  header_lines(filestr)
+    .find_map(|line| reg.captures(line))
+    .and_then(|caps| caps[1].trim_matches(['"', '\'']).parse().ok())
+}
+
+pub fn note_timezone(filestr: &str) -> Option<Tz> {
+  /* Only the header, a `Timezone:` line further down is part of the note, not a setting for it */
+  let reg = Regex::new(TZLINEREG).unwrap();
+  header_lines(filestr)
+    .find_map(|line| reg.captures(line))
+    .and_then(|caps| config::parse_tz(&caps[1]))
+}
+
+pub fn events_in(filestr: &str) -> Vec<(usize, Result<EventModel, EventParseError>)> {
+  events_in_with(filestr, &ParseConfig::default())
+}
+
+pub fn events_in_with(filestr: &str, config: &ParseConfig) -> Vec<(usize, Result<EventModel, EventParseError>)> {
+  events_in_note(filestr, &NoteMeta::parse(None, filestr), config)
+}
+
+pub fn events_in_note(filestr: &str, meta: &NoteMeta, config: &ParseConfig) -> Vec<(usize, Result<EventModel, EventParseError>)> {
+  /*
+   * Every line that looks like an event, paired with its 1-based line number so callers can report failures.
+   * Code, comments and frontmatter are skipped, an event in a code block is an example rather than a plan.
+   */
+  let config = config.for_note(meta);
+  let mut nesting = ListNesting::default();
+  let mut ret = Vec::new();
+  for (lineno, line) in markdown::content_lines(filestr) {
+    let depth = nesting.depth(line);
+    if !line_is_event(line) {
+      continue;
+    }
+    let em = EventModel::from_line_with(line.to_string(), &config)
+      .map(|mut em| {
+        em.set_depth(depth.unwrap_or_default());
+        em
+      })
+      .map_err(|e| e.with_line(lineno));
+    ret.push((lineno, em));
+  }
+  ret
 }
 
 
 #[cfg(test)]
 mod tests {
   use crate::*;
+  use crate::parsing::*;
 
   #[test]
   fn test_all() {
@@ -43,4 +299,155 @@ mod tests {
     let thin: Vec<String> = s.lines().filter(|&l| line_is_event(l)).map(|s| s.to_string()).collect();
     println!("{:#?}", thin);
   }
+
+  #[test]
+  fn test_event_tag_in() {
+    let rule = |filestr: &str| event_tag_in(filestr.as_bytes(), "event").unwrap().map(|m| (m.rule, m.line));
+    let example = std::fs::read_to_string("res/example.md").unwrap();
+    assert_eq!(rule(&example), Some((TagRule::TagsLine, 3)));
+
+    assert_eq!(rule("---\ntitle: Shows\ntags: [music, event]\n---\n"), Some((TagRule::Frontmatter, 3)));
+    assert_eq!(rule("---\ntags: \"#event/concert\"\n---\n"), Some((TagRule::Frontmatter, 2)));
+    assert_eq!(rule("---\ntags:\n  - music\n  - event\n---\n"), Some((TagRule::Frontmatter, 4)));
+    assert_eq!(rule("# Shows\nUpcoming #music #event/concert\n"), Some((TagRule::Heading, 2)));
+    assert_eq!(rule("tags: #event/festival\n"), Some((TagRule::TagsLine, 1)));
+    assert_eq!(rule("# Concerts\n\nTags: #event\n- [ ] (2 Nov) () () Polyphia\n"), Some((TagRule::TagsLine, 3)));
+
+    // Lists under other keys, other tags, and tags past the header don't count
+    assert_eq!(rule("---\naliases:\n  - event\n---\n"), None);
+    assert_eq!(rule("---\ntags: [events]\n---\n"), None);
+    assert_eq!(rule("Tags: #events #eventual\n"), None);
+    assert_eq!(rule("# Shows\n\nThis is an #event\n"), None);
+    assert_eq!(rule("Some text about an #event\n"), None);
+    assert_eq!(rule(&format!("{}Tags: #event\n", "text\n".repeat(40))), None);
+
+    assert_eq!(event_tag_in("Tags: #Concert/2024".as_bytes(), "concert").unwrap().map(|m| m.rule), Some(TagRule::TagsLine));
+    assert!(!file_is_event("Tags: #concert\n"));
+    assert!(file_is_event_with("Tags: #concert\n", &ParseConfig { event_tag: Some("concert".to_owned()), ..Default::default() }));
+  }
+
+  #[test]
+  fn test_events_in() {
+    let note = "Tags: #event\n\n- [ ] (2 Nov) () (Houston) Polyphia\nnot an event\n- [ ] (2 Nov) (25PM) () Bad time\n";
+    let events = parsing::events_in(note);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, 3);
+    assert!(events[0].1.is_ok());
+    assert_eq!(events[1].0, 5);
+    assert_eq!(events[1].1.as_ref().unwrap_err().line(), Some(5));
+  }
+
+  #[test]
+  fn test_note_timezone() {
+    let note = "Tags: #event\nTimezone: America/Chicago\n\n- [ ] (2 Nov 2023) (7 PM) (Houston) Polyphia\n- [ ] (3 Nov 2023) (7 PM PT) (LA) Polyphia\n";
+    let config = ParseConfig { default_tz: Some(Tz::UTC), ..Default::default() };
+    let events = parsing::events_in_with(note, &config);
+    assert_eq!(events[0].1.as_ref().unwrap().timezone(), Some(Tz::America__Chicago));
+    assert_eq!(events[1].1.as_ref().unwrap().timezone(), Some(Tz::America__Los_Angeles));
+
+    let events = parsing::events_in_with("- [ ] (2 Nov 2023) (7 PM) (Houston) Polyphia", &config);
+    assert_eq!(events[0].1.as_ref().unwrap().timezone(), Some(Tz::UTC));
+
+    // Past the header, like in a code block near the end, it's just text
+    let note = format!("Tags: #event\n{}```\nTimezone: America/Chicago\n```\n", "- [ ] (2 Nov 2023) (7 PM) () Polyphia\n".repeat(30));
+    assert_eq!(parsing::note_timezone(&note), None);
+  }
+
+  #[test]
+  fn test_skips_code_and_comments() {
+    let note = "\
+---
+tags: [event]
+---
+- [ ] (1 Nov 2023) () () Real
+```
+- [ ] (2 Nov 2023) () () Fenced
+```
+%%
+- [ ] (3 Nov 2023) () () Commented
+%%
+<!-- - [ ] (4 Nov 2023) () () Html -->
+<!--
+- [ ] (5 Nov 2023) () () Html block
+-->
+
+    - [ ] (6 Nov 2023) () () Indented code
+- [ ] (7 Nov 2023) () () Also real
+- [ ] (8 Nov) (25PM) () Broken
+";
+    let events = parsing::events_in(note);
+    let found: Vec<(usize, &str)> = events.iter()
+      .filter_map(|(line, em)| em.as_ref().ok().map(|em| (*line, em.title())))
+      .collect();
+    assert_eq!(found, vec![(4, "Real"), (17, "Also real")]);
+    // Failures keep the line they're on in the file
+    assert_eq!(events.last().unwrap().0, 18);
+    assert_eq!(events.last().unwrap().1.as_ref().unwrap_err().line(), Some(18));
+  }
+
+  #[test]
+  fn test_list_markers() {
+    let note = "\
+Tags: #event
+* [ ] (1 Nov 2023) () () Star
++ [ ] (2 Nov 2023) () () Plus
+1. [ ] (3 Nov 2023) () () Ordered
+2) [x] (4 Nov 2023) () () Paren
+> - [ ] (5 Nov 2023) () () Quoted
+> [!note] Festivals
+> - Weekend one
+>   - [ ] (6 Nov 2023) () () In a callout
+> > - [ ] (7 Nov 2023) () () Twice quoted
+- Tour
+\t- [ ] (8 Nov 2023) () () Tabbed
+    - [ ] (9 Nov 2023) () () Deeper
+  - [ ] (10 Nov 2023) () () Back up
+Some text - [ ] (11 Nov 2023) () () Not a list item
+-[ ] (12 Nov 2023) () () No space
+";
+    let events = parsing::events_in(note);
+    let found: Vec<(&str, usize, usize)> = events.iter()
+      .map(|(_, em)| em.as_ref().unwrap())
+      .map(|em| (em.title(), em.quote_depth(), em.depth()))
+      .collect();
+    assert_eq!(found, vec![
+      ("Star", 0, 0), ("Plus", 0, 0), ("Ordered", 0, 0), ("Paren", 0, 0),
+      ("Quoted", 1, 0), ("In a callout", 1, 1), ("Twice quoted", 2, 0),
+      ("Tabbed", 0, 1), ("Deeper", 0, 1), ("Back up", 0, 1),
+    ]);
+    assert_eq!(events[9].0, 14);
+
+    let em = events[5].1.as_ref().unwrap();
+    assert!(serde_json::to_string(em).unwrap().contains(r#""quote_depth":1,"depth":1"#));
+  }
+
+  #[test]
+  fn test_note_locale() {
+    let note = "---\nlang: de-DE\n---\nTags: #event\n- [ ] (3. März 2024) (20:00) (Berlin) Konzert\n";
+    assert_eq!(parsing::note_locale(note), Some(Locale::De));
+    let events = parsing::events_in_with(note, &ParseConfig::default());
+    assert_eq!(events[0].1.as_ref().unwrap().start_date(), chrono::NaiveDate::from_ymd_opt(2024, 3, 3).unwrap());
+
+    // Without the note saying so März isn't a month, unless the vault says so
+    let note = "Tags: #event\n- [ ] (3. März 2024) (20:00) (Berlin) Konzert\n";
+    assert!(parsing::events_in_with(note, &ParseConfig::default())[0].1.is_err());
+    let config = ParseConfig { locale: Locale::De, ..Default::default() };
+    assert!(parsing::events_in_with(note, &config)[0].1.is_ok());
+
+    let note = format!("Tags: #event\n{}Lang: de\n", "text\n".repeat(30));
+    assert_eq!(parsing::note_locale(&note), None);
+  }
+
+  #[test]
+  fn test_note_reference_date() {
+    // Year-less dates resolve around when the note was written, not when we happen to parse it
+    let note = "202310152010\nTags: #event\n\n- [ ] (7 Nov) (6PM-10PM) (House of Blues, Houston) Of Mice & Men\n- [ ] (15 Feb) () () Next winter\n";
+    let events = parsing::events_in_with(note, &ParseConfig::default());
+    assert_eq!(events[0].1.as_ref().unwrap().start_date(), chrono::NaiveDate::from_ymd_opt(2023, 11, 7).unwrap());
+    assert_eq!(events[1].1.as_ref().unwrap().start_date(), chrono::NaiveDate::from_ymd_opt(2024, 2, 15).unwrap());
+
+    let config = ParseConfig { reference_date: chrono::NaiveDate::from_ymd_opt(2026, 1, 1), ..Default::default() };
+    let events = parsing::events_in_with(note, &config);
+    assert_eq!(events[0].1.as_ref().unwrap().start_date(), chrono::NaiveDate::from_ymd_opt(2025, 11, 7).unwrap());
+  }
 }
\ No newline at end of file
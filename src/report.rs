@@ -0,0 +1,225 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::diagnostic::{Diagnostic, Severity};
+
+const TOOL_NAME: &str = "md-event-parser";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// Every rule id a Diagnostic can carry, with the one-liner SARIF viewers show next to it
+const RULES: [(&str, &str); 8] = [
+  ("not-event", "Line is not an event"),
+  ("regex-mismatch", "Event line is missing a (date) (time) (place) group"),
+  ("bad-date", "Date group couldn't be parsed"),
+  ("bad-time", "Time group couldn't be parsed"),
+  ("empty-title", "Event has no title"),
+  ("near-miss", "Line almost looks like an event"),
+  ("end-date-before-start", "Event ends on an earlier date than it starts"),
+  ("end-time-before-start", "Event ends at an earlier time than it starts"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+  #[default]
+  Text,
+  Github,
+  Sarif,
+  Json,
+}
+
+impl FromStr for ReportFormat {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<ReportFormat, String> {
+    match s {
+      "text" => Ok(ReportFormat::Text),
+      "github" => Ok(ReportFormat::Github),
+      "sarif" => Ok(ReportFormat::Sarif),
+      "json" => Ok(ReportFormat::Json),
+      other => Err(format!("unknown diagnostics format {other}, expected text, github, sarif or json")),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonDiagnostic<'a> {
+  file: String,
+  line: usize,
+  column: usize,
+  end_column: usize,
+  severity: &'static str,
+  rule: &'static str,
+  message: &'a str,
+  help: Option<&'a str>,
+}
+
+fn uri(diag: &Diagnostic) -> String {
+  /* Forward slashes whatever the OS, both GitHub and SARIF want repo-relative URIs */
+  diag.path.to_string_lossy().replace('\\', "/")
+}
+
+fn escape_data(s: &str) -> String {
+  s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(s: &str) -> String {
+  escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+pub fn github(diag: &Diagnostic) -> String {
+  /* https://docs.github.com/en/actions/using-workflow-commands-for-github-actions#setting-an-error-message */
+  let mut message = diag.message.clone();
+  if let Some(help) = &diag.help {
+    message.push_str("\nhelp: ");
+    message.push_str(help);
+  }
+  format!("::{} file={},line={},col={},endColumn={},title={}::{}",
+    diag.severity.as_str(),
+    escape_property(&uri(diag)),
+    diag.line,
+    diag.column(),
+    // GitHub's endColumn is the last highlighted column, ours is one past it
+    (diag.end_column() - 1).max(diag.column()),
+    escape_property(diag.rule),
+    escape_data(&message))
+}
+
+pub fn sarif(diags: &[Diagnostic]) -> serde_json::Value {
+  let rules: Vec<serde_json::Value> = RULES.iter()
+    .map(|(id, desc)| json!({ "id": id, "shortDescription": { "text": desc } }))
+    .collect();
+
+  let results: Vec<serde_json::Value> = diags.iter().map(|diag| {
+    let mut message = diag.message.clone();
+    if let Some(help) = &diag.help {
+      message.push_str(&format!("\nhelp: {help}"));
+    }
+    json!({
+      "ruleId": diag.rule,
+      "ruleIndex": RULES.iter().position(|(id, _)| *id == diag.rule),
+      "level": diag.severity.as_str(),
+      "message": { "text": message },
+      "locations": [{
+        "physicalLocation": {
+          "artifactLocation": { "uri": uri(diag) },
+          "region": {
+            "startLine": diag.line,
+            "startColumn": diag.column(),
+            "endColumn": diag.end_column(),
+            "snippet": { "text": diag.text },
+          },
+        },
+      }],
+    })
+  }).collect();
+
+  json!({
+    "$schema": SARIF_SCHEMA,
+    "version": "2.1.0",
+    "runs": [{
+      "tool": {
+        "driver": {
+          "name": TOOL_NAME,
+          "version": env!("CARGO_PKG_VERSION"),
+          "rules": rules,
+        },
+      },
+      // Our columns count chars, not the UTF-16 units SARIF assumes by default
+      "columnKind": "unicodeCodePoints",
+      "results": results,
+    }],
+  })
+}
+
+pub fn write_report<W: Write>(out: &mut W, format: ReportFormat, diags: &[Diagnostic], color: bool) -> io::Result<()> {
+  match format {
+    ReportFormat::Text => {
+      for diag in diags {
+        writeln!(out, "{}", diag.render(color))?;
+      }
+    },
+    ReportFormat::Github => {
+      for diag in diags {
+        writeln!(out, "{}", github(diag))?;
+      }
+    },
+    ReportFormat::Sarif => {
+      serde_json::to_writer_pretty(&mut *out, &sarif(diags))?;
+      writeln!(out)?;
+    },
+    ReportFormat::Json => {
+      let list: Vec<JsonDiagnostic> = diags.iter().map(|diag| JsonDiagnostic {
+        file: uri(diag),
+        line: diag.line,
+        column: diag.column(),
+        end_column: diag.end_column(),
+        severity: diag.severity.as_str(),
+        rule: diag.rule,
+        message: &diag.message,
+        help: diag.help.as_deref(),
+      }).collect();
+      serde_json::to_writer_pretty(&mut *out, &list)?;
+      writeln!(out)?;
+    },
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+
+  use crate::report::*;
+  use crate::config::ParseConfig;
+  use crate::lint;
+
+  fn diags() -> Vec<Diagnostic> {
+    lint::lint_note(Path::new("Concerts/2024.md"), "Tags: #event\n- [ ] (15 Feb 2024) (6ish) (White Oak, Houston) The Plot in You\n", &ParseConfig::default())
+  }
+
+  #[test]
+  fn test_github() {
+    let line = github(&diags()[0]);
+    assert_eq!(line, "::error file=Concerts/2024.md,line=2,col=22,endColumn=25,title=bad-time::invalid time `6ish`%0Ahelp: expected `6 PM`, `6:30 PM`, `6-10PM` or `18:00-22:30`");
+    assert_eq!(escape_property("a,b:c%"), "a%2Cb%3Ac%25");
+  }
+
+  #[test]
+  fn test_sarif() {
+    let sarif = sarif(&diags());
+    assert_eq!(sarif["version"], "2.1.0");
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "bad-time");
+    assert_eq!(result["level"], "error");
+    let rule_index = result["ruleIndex"].as_u64().unwrap() as usize;
+    assert_eq!(sarif["runs"][0]["tool"]["driver"]["rules"][rule_index]["id"], "bad-time");
+    let region = &result["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 2);
+    assert_eq!(region["startColumn"], 22);
+    assert_eq!(region["endColumn"], 26);
+  }
+
+  #[test]
+  fn test_json() {
+    let mut buf = Vec::new();
+    write_report(&mut buf, ReportFormat::Json, &diags(), false).unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+    assert_eq!(parsed[0]["file"], "Concerts/2024.md");
+    assert_eq!(parsed[0]["severity"], "error");
+    assert_eq!(parsed[0]["rule"], "bad-time");
+    assert_eq!(parsed[0]["column"], 22);
+  }
+
+  #[test]
+  fn test_rules_cover_diagnostics() {
+    let lines = "- [ ] (x) () () A\n- [ ] (2 Nov) (x) () B\n- [ ] (2 Nov) () () \n- [ ] (2 Nov) C\n- [ ] (3-2 Nov) () () D\n- [ ] (2 Nov) (9-8PM) () E\n";
+    let diags = lint::lint_note(Path::new("a.md"), lines, &ParseConfig::default());
+    assert_eq!(diags.len(), 6);
+    for diag in diags {
+      assert!(RULES.iter().any(|(id, _)| *id == diag.rule), "{} missing from RULES", diag.rule);
+    }
+  }
+}
@@ -0,0 +1,267 @@
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern, PatternError};
+use serde::Serialize;
+
+use crate::config::ParseConfig;
+use crate::model::{EventModel, EventParseError};
+use crate::note::NoteMeta;
+use crate::parsing::{self, TagMatch};
+
+// Obsidian keeps its config and deleted notes in these, neither is ever a real note
+const SKIP_DIRS: [&str; 2] = [".obsidian", ".trash"];
+
+const GLOB_OPTS: MatchOptions = MatchOptions {
+  case_sensitive: true,
+  require_literal_separator: true,
+  require_literal_leading_dot: false,
+};
+
+#[derive(Debug, Serialize)]
+pub struct SourcedEvent {
+  pub path: PathBuf,
+  pub line: usize,
+  pub note: NoteMeta,
+  #[serde(flatten)]
+  pub event: EventModel,
+}
+
+#[derive(Debug)]
+pub struct ScanFailure {
+  pub path: PathBuf,
+  pub line: usize,
+  pub text: String, // The offending line as written, error spans index into this
+  pub error: EventParseError,
+}
+
+impl ScanFailure {
+  pub fn column(&self) -> usize {
+    self.error.loc().column(&self.text).unwrap_or(1)
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct VaultScan {
+  pub events: Vec<SourcedEvent>,
+  pub failures: Vec<ScanFailure>,
+  pub unreadable: Vec<(PathBuf, io::Error)>, // Notes that couldn't be opened or aren't UTF-8, the rest of the vault is still scanned
+}
+
+impl VaultScan {
+  pub fn extend(&mut self, other: VaultScan) {
+    self.events.extend(other.events);
+    self.failures.extend(other.failures);
+    self.unreadable.extend(other.unreadable);
+  }
+
+  pub fn add_note(&mut self, root: &Path, path: &Path, config: &ParseConfig) {
+    match scan_note(root, path, config) {
+      Ok(scan) => self.extend(scan),
+      Err(error) => self.unreadable.push((path.to_path_buf(), error)),
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct VaultScanner {
+  include: Vec<Pattern>,
+  exclude: Vec<Pattern>,
+  config: ParseConfig,
+}
+
+impl VaultScanner {
+  pub fn new() -> VaultScanner {
+    VaultScanner::default()
+  }
+
+  /* Globs are matched against the path relative to the vault root, e.g. `Concerts/**/*.md` */
+  pub fn include(mut self, pattern: &str) -> Result<VaultScanner, PatternError> {
+    self.include.push(Pattern::new(pattern)?);
+    Ok(self)
+  }
+
+  pub fn exclude(mut self, pattern: &str) -> Result<VaultScanner, PatternError> {
+    self.exclude.push(Pattern::new(pattern)?);
+    Ok(self)
+  }
+
+  pub fn config(mut self, config: ParseConfig) -> VaultScanner {
+    self.config = config;
+    self
+  }
+
+  fn wants(&self, rel: &Path) -> bool {
+    let included = self.include.is_empty() || self.include.iter().any(|p| p.matches_path_with(rel, GLOB_OPTS));
+    included && !self.exclude.iter().any(|p| p.matches_path_with(rel, GLOB_OPTS))
+  }
+
+  pub fn notes(&self, root: &Path) -> io::Result<Vec<PathBuf>> {
+    /* Every `.md` file under root that passes the globs, sorted so output is stable between runs */
+    let mut found = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+      for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+          if !SKIP_DIRS.iter().any(|skip| entry.file_name() == *skip) {
+            stack.push(path);
+          }
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+          let rel = path.strip_prefix(root).unwrap_or(&path);
+          if self.wants(rel) {
+            found.push(path);
+          }
+        }
+      }
+    }
+
+    found.sort();
+    Ok(found)
+  }
+
+  pub fn scan(&self, root: &Path) -> io::Result<VaultScan> {
+    /* Only fails if the vault itself can't be walked, a bad note ends up in `unreadable` */
+    let mut ret = VaultScan::default();
+    for path in self.notes(root)? {
+      ret.add_note(root, &path, &self.config);
+    }
+    Ok(ret)
+  }
+}
+
+pub fn note_source(root: &Path, path: &Path) -> String {
+  /* What uids are namespaced by, the note's path inside the vault with `/` separators whatever the OS */
+  let rel = path.strip_prefix(root).unwrap_or(path);
+  rel.components()
+    .map(|c| c.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+pub fn read_tagged(path: &Path, config: &ParseConfig) -> io::Result<Option<(String, TagMatch)>> {
+  /* The whole note only if its header tags it as an event note, untagged notes are never read past the header */
+  let found = parsing::event_tag_in(BufReader::new(fs::File::open(path)?), config.event_tag())?;
+  match found {
+    Some(found) => Ok(Some((fs::read_to_string(path)?, found))),
+    None => Ok(None),
+  }
+}
+
+pub fn scan_note(root: &Path, path: &Path, config: &ParseConfig) -> io::Result<VaultScan> {
+  /* Notes that aren't tagged as events come back empty rather than as an error */
+  let mut ret = VaultScan::default();
+  let Some((filestr, found)) = read_tagged(path, config)? else {
+    return Ok(ret);
+  };
+
+  let source = note_source(root, path);
+  let meta = NoteMeta { tagged_by: Some(found.rule), ..NoteMeta::parse(Some(path), &filestr) };
+  let lines: Vec<&str> = filestr.lines().collect();
+  for (line, res) in parsing::events_in_note(&filestr, &meta, config) {
+    match res {
+      Ok(mut event) => {
+        event.set_source(&source);
+        ret.events.push(SourcedEvent { path: path.to_path_buf(), line, note: meta.clone(), event });
+      },
+      Err(error) => ret.failures.push(ScanFailure { path: path.to_path_buf(), line, text: lines[line - 1].to_string(), error }),
+    }
+  }
+  Ok(ret)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use crate::vault::*;
+
+  pub(crate) fn sourced(event: EventModel) -> SourcedEvent {
+    /* An event as if it were on the first line of `Concerts.md`, for the output formats' tests */
+    SourcedEvent { path: PathBuf::from("Concerts.md"), line: 1, note: NoteMeta::default(), event }
+  }
+
+  fn make_vault(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("md-event-parser-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let event_note = "Tags: #event\n\n- [ ] (2 Nov 2023) () (Houston) Polyphia\n";
+
+    for dir in ["Concerts/2023", ".obsidian", ".trash", "Templates"] {
+      fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    fs::write(root.join("Concerts/2023/polyphia.md"), event_note).unwrap();
+    fs::write(root.join("Concerts/plain.md"), "Just a note\n- [ ] (2 Nov 2023) () () Not scanned\n").unwrap();
+    fs::write(root.join("Concerts/notes.txt"), event_note).unwrap();
+    fs::write(root.join(".obsidian/cached.md"), event_note).unwrap();
+    fs::write(root.join(".trash/old.md"), event_note).unwrap();
+    fs::write(root.join("Templates/event.md"), event_note).unwrap();
+    root
+  }
+
+  #[test]
+  fn test_scan_skips_obsidian_dirs() {
+    let root = make_vault("skip");
+    let scan = VaultScanner::new().scan(&root).unwrap();
+    let paths: Vec<&Path> = scan.events.iter().map(|e| e.path.strip_prefix(&root).unwrap()).collect();
+    assert_eq!(paths, vec![Path::new("Concerts/2023/polyphia.md"), Path::new("Templates/event.md")]);
+    assert_eq!(scan.events[0].line, 3);
+    assert_eq!(scan.events[0].note.tagged_by, Some(parsing::TagRule::TagsLine));
+    assert!(scan.failures.is_empty());
+    assert!(scan.unreadable.is_empty());
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn test_scan_keeps_going_past_unreadable() {
+    let root = make_vault("unreadable");
+    fs::write(root.join("Concerts/binary.md"), b"Tags: #event\n\xff\xfe\n").unwrap();
+    let scan = VaultScanner::new().scan(&root).unwrap();
+    assert_eq!(scan.events.len(), 2);
+    assert_eq!(scan.unreadable.len(), 1);
+    assert_eq!(scan.unreadable[0].0, root.join("Concerts/binary.md"));
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn test_scan_tag() {
+    let root = make_vault("tag");
+    fs::write(root.join("Concerts/tour.md"), "---\ntags:\n  - concert/2024\n---\n- [ ] (2 Nov 2023) () () Tour\n").unwrap();
+    let config = ParseConfig { event_tag: Some("concert".to_owned()), ..Default::default() };
+    let scan = VaultScanner::new().config(config).scan(&root).unwrap();
+    let titles: Vec<&str> = scan.events.iter().map(|e| e.event.title()).collect();
+    assert_eq!(titles, vec!["Tour"]);
+    assert_eq!(scan.events[0].note.tagged_by, Some(parsing::TagRule::Frontmatter));
+    assert_eq!(scan.events[0].line, 5);
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn test_uid_uses_vault_relative_path() {
+    let root = make_vault("uid");
+    let scan = VaultScanner::new().scan(&root).unwrap();
+    let copy = make_vault("uid-copy");
+    let copy_scan = VaultScanner::new().scan(&copy).unwrap();
+
+    assert_eq!(note_source(&root, &scan.events[0].path), "Concerts/2023/polyphia.md");
+    assert_eq!(scan.events[0].event.uid(), copy_scan.events[0].event.uid());
+    assert_ne!(scan.events[0].event.uid(), scan.events[1].event.uid());
+    fs::remove_dir_all(&root).unwrap();
+    fs::remove_dir_all(&copy).unwrap();
+  }
+
+  #[test]
+  fn test_scan_globs() {
+    let root = make_vault("globs");
+    let scanner = VaultScanner::new().include("Concerts/**/*.md").unwrap();
+    let notes = scanner.notes(&root).unwrap();
+    assert_eq!(notes, vec![root.join("Concerts/2023/polyphia.md"), root.join("Concerts/plain.md")]);
+
+    let scanner = VaultScanner::new().exclude("Templates/*").unwrap();
+    let notes = scanner.notes(&root).unwrap();
+    assert_eq!(notes, vec![root.join("Concerts/2023/polyphia.md"), root.join("Concerts/plain.md")]);
+    fs::remove_dir_all(&root).unwrap();
+  }
+}